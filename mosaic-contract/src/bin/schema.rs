@@ -1,10 +1,10 @@
 use cosmwasm_schema::write_api;
-use cw721_base::msg::InstantiateMsg;
-use mosaic_contract::msg::ExecuteMsg;
+use mosaic_contract::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -1,101 +1,135 @@
-use cosmwasm_std::{Coin, Uint128};
-use lazy_static::lazy_static;
+use cosmwasm_std::{Addr, Coin, Decimal, StdError, StdResult, Uint128};
 
-// Read config.json at compile time
-const CONFIG_STR: &str = include_str!("../../config.json");
-
-lazy_static! {
-    static ref CONFIG: Config = serde_json::from_str(CONFIG_STR).expect("Failed to parse config.json");
-}
+use crate::state::{FeeRecipient, FeeShare, MosaicConfig, PositionPricing, VolumeDiscount};
 
 pub mod fees {
     use super::*;
-    use std::cmp::max;
 
     // Time constants in seconds
     pub const HOUR: u64 = 3600;
     pub const DAY: u64 = 24 * HOUR;
 
-    // Fee tiers in ustars (1 STARS = 1_000_000 ustars)
+    // Default fee tiers in ustars (1 STARS = 1_000_000 ustars)
     pub const FEE_TIER_1H: u128 = 5_000_000;  // 5 STARS for < 1h
     pub const FEE_TIER_12H: u128 = 10_000_000; // 10 STARS for < 12h
     pub const FEE_TIER_24H: u128 = 15_000_000; // 15 STARS for < 24h
 
-    pub fn calculate_fee(expiration_duration: u64) -> Coin {
+    // Position weight of an unweighted tile, in basis points
+    pub const WEIGHT_BASE: u32 = 10_000;
+
+    /// Fee for locking a pixel for `expiration_duration` seconds.
+    /// Only integer arithmetic is used so the result is identical on every node.
+    pub fn calculate_fee(config: &MosaicConfig, expiration_duration: u64) -> StdResult<Coin> {
+        let tiers = &config.fee_tiers;
         let amount = if expiration_duration < HOUR {
-            FEE_TIER_1H
+            tiers.tier_1h
         } else if expiration_duration < 12 * HOUR {
-            FEE_TIER_12H
+            tiers.tier_12h
         } else if expiration_duration < DAY {
-            FEE_TIER_24H
+            tiers.tier_24h
         } else {
             // For durations > 24h, scale quadratically with per-second granularity
-            // Base fee is 15 STARS for 24h
-            // Formula: base_fee * (duration/24h)^2
-            let seconds_ratio = expiration_duration as f64 / DAY as f64;
-            let scale = seconds_ratio * seconds_ratio; // quadratic scaling
-            let scaled_amount = (FEE_TIER_24H as f64 * scale) as u128;
-            max(scaled_amount, FEE_TIER_24H) // Never go below base fee
+            // Base fee is the 24h tier
+            // Formula: base_fee * duration^2 / (24h)^2, rounded down
+            let duration = expiration_duration as u128;
+            let scaled_amount = tiers
+                .tier_24h
+                .checked_multiply_ratio(duration * duration, (DAY * DAY) as u128)
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            scaled_amount.max(tiers.tier_24h) // Never go below base fee
         };
 
-        Coin {
-            amount: Uint128::from(amount),
-            denom: CONFIG.mosaic.fees.base_fee.denom.clone(),
-        }
+        Ok(Coin {
+            amount,
+            denom: config.denom.clone(),
+        })
     }
 
-    pub fn developer_royalties() -> u8 {
-        CONFIG.mosaic.fees.developer_royalties
-    }
+    /// Price weight of a tile in basis points. Zones take precedence over
+    /// corners, and corners over edges.
+    pub fn position_weight(config: &MosaicConfig, pricing: Option<&PositionPricing>, tile_id: u32) -> u32 {
+        let Some(pricing) = pricing else {
+            return WEIGHT_BASE;
+        };
+        let (x, y) = (tile_id % pricing.tiles_per_row, tile_id / pricing.tiles_per_row);
+        if let Some(zone) = pricing.zones.iter().find(|zone| zone.contains(x, y)) {
+            return zone.weight;
+        }
 
-    pub fn developer_address() -> String {
-        CONFIG.mosaic.fees.developer_address.clone()
+        let last_row = (config.total_tiles - 1) / pricing.tiles_per_row;
+        let on_column_edge = x == 0 || x == pricing.tiles_per_row - 1;
+        let on_row_edge = y == 0 || y == last_row;
+        match (on_column_edge, on_row_edge) {
+            (true, true) => pricing.corner_weight,
+            (true, false) | (false, true) => pricing.edge_weight,
+            (false, false) => WEIGHT_BASE,
+        }
     }
-}
-
-pub mod tile {
-    use super::*;
 
-    pub fn total_tiles() -> u32 {
-        CONFIG.mosaic.tile.total_tiles
+    /// Fee for locking a pixel of `tile_id`, the duration fee scaled by the
+    /// position weight of the tile and rounded down
+    pub fn calculate_position_fee(
+        config: &MosaicConfig,
+        pricing: Option<&PositionPricing>,
+        tile_id: u32,
+        expiration_duration: u64,
+    ) -> StdResult<Coin> {
+        let fee = calculate_fee(config, expiration_duration)?;
+        let weight = position_weight(config, pricing, tile_id);
+        let amount = fee
+            .amount
+            .checked_multiply_ratio(weight, WEIGHT_BASE)
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+        Ok(Coin { amount, ..fee })
     }
 
-    pub fn total_pixels_per_tile() -> u32 {
-        CONFIG.mosaic.tile.total_pixels_per_tile
+    /// Discount in basis points of a batch painting `pixel_count` pixels,
+    /// from the largest tier the batch reaches
+    pub fn volume_discount(discounts: &[VolumeDiscount], pixel_count: usize) -> u32 {
+        discounts
+            .iter()
+            .rev()
+            .find(|tier| tier.min_pixels as usize <= pixel_count)
+            .map_or(0, |tier| tier.discount)
     }
-}
-
-// Config structs
-use serde::Deserialize;
-use std::str::FromStr;
 
-#[derive(Deserialize)]
-struct Config {
-    mosaic: MosaicConfig,
-}
+    /// Applies a discount in basis points to a fee, rounded down
+    pub fn apply_discount(amount: Uint128, discount: u32) -> Uint128 {
+        amount.multiply_ratio(WEIGHT_BASE - discount, WEIGHT_BASE)
+    }
 
-#[derive(Deserialize)]
-struct MosaicConfig {
-    fees: Fees,
-    tile: Tile,
-}
+    /// Returns the (developer, owner) shares of a fee
+    pub fn split_fee(config: &MosaicConfig, amount: Uint128) -> (Uint128, Uint128) {
+        let developer_amount = amount.multiply_ratio(config.developer_royalties as u128, 100u128);
+        (developer_amount, amount - developer_amount)
+    }
 
-#[derive(Deserialize)]
-struct Fees {
-    base_fee: Fee,
-    developer_royalties: u8,
-    developer_address: String,
-    mint_price: Fee,
-}
+    /// Splits a fee between the recipients of `shares`, paying `TileOwner`
+    /// shares to `owner`. Shares are rounded down and the remainder goes to
+    /// the first recipient, everything goes to `owner` without shares.
+    pub fn split_shares(amount: Uint128, shares: &[FeeShare], owner: &Addr) -> Vec<(Addr, Uint128)> {
+        let mut amounts: Vec<_> = shares
+            .iter()
+            .map(|share| {
+                let recipient = match &share.recipient {
+                    FeeRecipient::TileOwner => owner.clone(),
+                    FeeRecipient::Address(address) => address.clone(),
+                };
+                (recipient, amount.multiply_ratio(share.weight, WEIGHT_BASE))
+            })
+            .collect();
+
+        let distributed: Uint128 = amounts.iter().map(|(_, amount)| amount).sum();
+        match amounts.first_mut() {
+            Some((_, first)) => *first += amount - distributed,
+            None => amounts.push((owner.clone(), amount)),
+        }
+        amounts
+    }
 
-#[derive(Deserialize)]
-struct Fee {
-    amount: String,
-    denom: String,
+    /// Returns the (royalty, owner) shares of a fee for a royalty share, rounded down
+    pub fn split_royalty(amount: Uint128, share: Decimal) -> (Uint128, Uint128) {
+        let royalty_amount = amount.multiply_ratio(share.atomics(), Decimal::one().atomics());
+        (royalty_amount, amount - royalty_amount)
+    }
 }
-
-#[derive(Deserialize)]
-struct Tile {
-    total_tiles: u32,
-    total_pixels_per_tile: u32,
-} 
\ No newline at end of file
@@ -1,13 +1,38 @@
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, BankMsg, Coin, to_json_binary, from_json, Empty};
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{
+    Addr, Api, Decimal, Deps, DepsMut, Env, Event, HexBinary, MessageInfo, Response, BankMsg, Coin,
+    StdError, StdResult, Storage, Uint128,
+};
 use sg_std::StargazeMsgWrapper;
-use cw721::OwnerOfResponse;
+use cw721_base::state::TokenInfo;
 use crate::{
     error::ContractError,
-    msg::PixelUpdate,
-    types::TileMetadata,
+    msg::{
+        EarningsResponse, ExecuteMsg, FeeEstimateResponse, MosaicConfigMsg, PauseStatus,
+        PixelUpdate, TileHashResponse, UpdateConfigMsg,
+    },
+    state::{
+        FeeRecipient, FeeShare, MosaicConfig, PositionPricing, VolumeDiscount, CONFIG, EARNINGS,
+        FEE_SPLIT, PAUSE_STATUS, POSITION_PRICING, TILE_HASHES, VOLUME_DISCOUNTS,
+    },
+    types::{Extension, TileMetadata},
     constants::fees,
+    events::{pixel_update_event, PixelId},
 };
 
+/// Maximum number of pixels that can be updated in a single batch
+pub const MAX_BATCH_SIZE: usize = 500;
+
+/// Maximum number of price zones, bounding the work of pricing each pixel
+pub const MAX_PRICE_ZONES: usize = 32;
+
+/// Maximum number of volume discount tiers
+pub const MAX_VOLUME_DISCOUNTS: usize = 10;
+
+/// Maximum number of rental fee recipients
+pub const MAX_FEE_SHARES: usize = 10;
+
 pub fn execute_set_pixel_color(
     deps: DepsMut,
     env: Env,
@@ -15,80 +40,729 @@ pub fn execute_set_pixel_color(
     current_tile_metadata: Vec<u8>,
     pixel_update: PixelUpdate,
 ) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pricing = POSITION_PRICING.may_load(deps.storage)?;
+
     // Calculate expiration duration and required fee
     let duration = pixel_update.expiration.saturating_sub(env.block.time.seconds());
-    let required_fee = fees::calculate_fee(duration);
+    let tile_id = pixel_update.pixel_id / config.pixels_per_tile;
+    let required_fee = fees::calculate_position_fee(&config, pricing.as_ref(), tile_id, duration)?;
+
+    // Validate payment
+    assert_payment(&info, &required_fee)?;
+
+    // Parse the current tile metadata and check it against the stored hash
+    let mut tile_metadata = parse_tile_metadata(&current_tile_metadata)?;
+    verify_tile_hash(deps.storage, &config, &tile_metadata)?;
+
+    // Verify pixel availability and apply the update
+    apply_pixel_update(&env, &mut tile_metadata, &pixel_update)?;
+
+    // Get the owner of the tile (NFT)
+    let owner = tile_owner(deps.storage, tile_metadata.tile_id)?;
+
+    // Store the updated metadata and its hash
+    let tile_hash = save_tile_metadata(deps.storage, &tile_metadata)?;
+    let event = pixel_event(&tile_metadata, pixel_update.pixel_id)?;
+
+    // Calculate fee distribution
+    let (royalty_address, royalty_share) = rental_royalty(deps.storage, &config)?;
+    let (developer_amount, owner_amount) = fees::split_royalty(required_fee.amount, royalty_share);
+    let shares = FEE_SPLIT.may_load(deps.storage)?.unwrap_or_default();
+    let mut recipient_amounts: BTreeMap<Addr, Uint128> = BTreeMap::new();
+    for (recipient, amount) in fees::split_shares(owner_amount, &shares, &owner) {
+        *recipient_amounts.entry(recipient).or_default() += amount;
+    }
+    let recipient_fees = recipient_fee_attributes(&recipient_amounts);
+
+    // Credit the fee distribution to the earnings ledger
+    credit_earnings(
+        deps.storage,
+        &required_fee.denom,
+        (royalty_address.clone(), developer_amount),
+        recipient_amounts,
+    )?;
+
+    // Return response with updated metadata
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "set_pixel_color")
+        .add_attribute("tile_id", tile_metadata.tile_id.to_string())
+        .add_attribute("tile_hash", tile_hash.to_hex())
+        .add_attribute("pixel_id", pixel_update.pixel_id.to_string())
+        .add_attribute("color", format!("{:?}", pixel_update.color))
+        .add_attribute("expiration", pixel_update.expiration.to_string())
+        .add_attribute("duration", duration.to_string())
+        .add_attribute("fee", required_fee.amount.to_string())
+        .add_attribute("royalty_address", royalty_address)
+        .add_attribute("developer_fee", developer_amount)
+        .add_attribute("owner_fee", owner_amount)
+        .add_attributes(recipient_fees))
+}
+
+pub fn execute_set_pixel_colors(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    current_tiles_metadata: Vec<Vec<u8>>,
+    updates: Vec<PixelUpdate>,
+) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    if updates.is_empty() {
+        return Err(ContractError::InvalidPixelUpdate("No pixel updates".to_string()));
+    }
+    if updates.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::InvalidPixelUpdate(format!(
+            "Cannot update more than {MAX_BATCH_SIZE} pixels at once"
+        )));
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let pricing = POSITION_PRICING.may_load(deps.storage)?;
+    let discounts = VOLUME_DISCOUNTS.may_load(deps.storage)?.unwrap_or_default();
+    let discount = fees::volume_discount(&discounts, updates.len());
+
+    // Parse and verify every affected tile once
+    let mut tiles: BTreeMap<u32, TileMetadata> = BTreeMap::new();
+    for data in &current_tiles_metadata {
+        let tile_metadata = parse_tile_metadata(data)?;
+        verify_tile_hash(deps.storage, &config, &tile_metadata)?;
+        tiles.insert(tile_metadata.tile_id, tile_metadata);
+    }
+
+    // Apply all updates in memory, accumulating the fee owed to each tile
+    let now = env.block.time.seconds();
+    let mut tile_fees: BTreeMap<u32, Uint128> = BTreeMap::new();
+    let mut pixel_fees = vec![];
+    let mut total_fee = Uint128::zero();
+    let mut denom = String::new();
+    for update in &updates {
+        let tile_id = update.pixel_id / config.pixels_per_tile;
+        let tile_metadata = tiles.get_mut(&tile_id).ok_or_else(|| {
+            ContractError::InvalidPixelUpdate(format!("Missing metadata for tile {tile_id}"))
+        })?;
+        apply_pixel_update(&env, tile_metadata, update)?;
+
+        let duration = update.expiration.saturating_sub(now);
+        let fee = fees::calculate_position_fee(&config, pricing.as_ref(), tile_id, duration)?;
+        let amount = fees::apply_discount(fee.amount, discount);
+        let tile_fee = tile_fees.entry(tile_id).or_default();
+        *tile_fee = tile_fee.checked_add(amount).map_err(StdError::from)?;
+        total_fee = total_fee.checked_add(amount).map_err(StdError::from)?;
+        pixel_fees.push((format!("pixel_fee_{}", update.pixel_id), amount.to_string()));
+        denom = fee.denom;
+    }
+
+    // Validate payment against the aggregated fee
+    let required_fee = Coin {
+        denom,
+        amount: total_fee,
+    };
+    assert_payment(&info, &required_fee)?;
+
+    // Split each tile's share between the royalty recipient and the tile owner
+    let (royalty_address, royalty_share) = rental_royalty(deps.storage, &config)?;
+    let shares = FEE_SPLIT.may_load(deps.storage)?.unwrap_or_default();
+    let mut developer_amount = Uint128::zero();
+    let mut recipient_amounts: BTreeMap<Addr, Uint128> = BTreeMap::new();
+    for (tile_id, amount) in &tile_fees {
+        let (developer_share, owner_share) = fees::split_royalty(*amount, royalty_share);
+        developer_amount += developer_share;
+
+        let owner = tile_owner(deps.storage, *tile_id)?;
+        for (recipient, amount) in fees::split_shares(owner_share, &shares, &owner) {
+            *recipient_amounts.entry(recipient).or_default() += amount;
+        }
+    }
+    let owner_amount = required_fee.amount - developer_amount;
+    let recipient_fees = recipient_fee_attributes(&recipient_amounts);
+
+    // Store the updated tiles and their hashes
+    let mut tile_hashes = vec![];
+    for tile_id in tile_fees.keys() {
+        let tile_hash = save_tile_metadata(deps.storage, &tiles[tile_id])?;
+        tile_hashes.push((format!("tile_hash_{tile_id}"), tile_hash.to_hex()));
+    }
+    let events = updates
+        .iter()
+        .map(|update| pixel_event(&tiles[&(update.pixel_id / config.pixels_per_tile)], update.pixel_id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    credit_earnings(
+        deps.storage,
+        &required_fee.denom,
+        (royalty_address.clone(), developer_amount),
+        recipient_amounts,
+    )?;
+
+    Ok(Response::new()
+        .add_events(events)
+        .add_attribute("action", "set_pixel_colors")
+        .add_attribute("pixel_count", updates.len().to_string())
+        .add_attribute("tile_count", tile_fees.len().to_string())
+        .add_attributes(tile_hashes)
+        .add_attribute("volume_discount", discount.to_string())
+        .add_attributes(pixel_fees)
+        .add_attribute("fee", required_fee.amount.to_string())
+        .add_attribute("royalty_address", royalty_address)
+        .add_attribute("developer_fee", developer_amount)
+        .add_attribute("owner_fee", owner_amount)
+        .add_attributes(recipient_fees))
+}
+
+pub fn execute_mint_tile(
+    deps: DepsMut,
+    info: MessageInfo,
+    tile_id: u32,
+) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Verify the position exists on the mosaic
+    if tile_id >= config.total_tiles {
+        return Err(ContractError::InvalidTileId {});
+    }
 
     // Validate payment
-    if info.funds.len() != 1 || info.funds[0] != required_fee {
+    let mint_price = Coin {
+        denom: config.denom.clone(),
+        amount: config.mint_price,
+    };
+    if mint_price.amount.is_zero() {
+        if !info.funds.is_empty() {
+            return Err(ContractError::InvalidFee {
+                expected: mint_price,
+                received: info.funds.first().cloned(),
+            });
+        }
+    } else {
+        assert_payment(&info, &mint_price)?;
+    }
+
+    // Mint the tile to the sender with default metadata
+    let base = sg721_base::Sg721Contract::<Extension>::default();
+    let token_id = tile_id.to_string();
+    let token = TokenInfo {
+        owner: info.sender.clone(),
+        approvals: vec![],
+        token_uri: None,
+        extension: Extension {
+            tile_metadata: TileMetadata::new(tile_id, config.pixels_per_tile).to_bytes(),
+        },
+    };
+    base.parent
+        .tokens
+        .update(deps.storage, &token_id, |old| match old {
+            Some(_) => Err(ContractError::TileAlreadyMinted { tile_id }),
+            None => Ok(token),
+        })?;
+    base.parent.increment_tokens(deps.storage)?;
+
+    // Split proceeds between the developer and the collection creator
+    let (developer_amount, creator_amount) = fees::split_fee(&config, mint_price.amount);
+    let creator = deps.api.addr_validate(&base.collection_info.load(deps.storage)?.creator)?;
+    credit_earnings(
+        deps.storage,
+        &mint_price.denom,
+        (config.developer_address.clone(), developer_amount),
+        BTreeMap::from([(creator, creator_amount)]),
+    )?;
+
+    // The standard cw721 mint attributes, so indexers pick up the new token
+    Ok(Response::new()
+        .add_attribute("action", "mint")
+        .add_attribute("minter", info.sender.clone())
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attribute("tile_id", tile_id.to_string())
+        .add_attribute("price", mint_price.amount)
+        .add_attribute("developer_fee", developer_amount)
+        .add_attribute("creator_fee", creator_amount))
+}
+
+pub fn execute_withdraw(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Option<Coin>,
+) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    let mut earnings = EARNINGS.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+
+    let withdrawn = match amount {
+        None => std::mem::take(&mut earnings),
+        Some(requested) => {
+            let balance = earnings
+                .iter_mut()
+                .find(|coin| coin.denom == requested.denom)
+                .ok_or(ContractError::NoEarnings {})?;
+            balance.amount = balance.amount.checked_sub(requested.amount).map_err(|_| {
+                ContractError::InsufficientEarnings {
+                    requested: requested.clone(),
+                    available: balance.clone(),
+                }
+            })?;
+            earnings.retain(|coin| !coin.amount.is_zero());
+            vec![requested]
+        }
+    };
+    if withdrawn.iter().all(|coin| coin.amount.is_zero()) {
+        return Err(ContractError::NoEarnings {});
+    }
+
+    if earnings.is_empty() {
+        EARNINGS.remove(deps.storage, &info.sender);
+    } else {
+        EARNINGS.save(deps.storage, &info.sender, &earnings)?;
+    }
+
+    let amount = withdrawn.iter().map(Coin::to_string).collect::<Vec<_>>().join(",");
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: withdrawn,
+        })
+        .add_attribute("action", "withdraw")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    update: UpdateConfigMsg,
+) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)
+        .map_err(|_| ContractError::Unauthorized {})?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let config = validate_config(
+        deps.api,
+        MosaicConfigMsg {
+            denom: update.denom.unwrap_or(config.denom),
+            fee_tiers: update.fee_tiers.unwrap_or(config.fee_tiers),
+            developer_address: update
+                .developer_address
+                .unwrap_or_else(|| config.developer_address.to_string()),
+            developer_royalties: update.developer_royalties.unwrap_or(config.developer_royalties),
+            mint_price: update.mint_price.unwrap_or(config.mint_price),
+            total_tiles: config.total_tiles,
+            pixels_per_tile: config.pixels_per_tile,
+        },
+    )?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let event = Event::new("update_config").add_attribute("sender", info.sender);
+    Ok(Response::new().add_event(event))
+}
+
+pub fn execute_update_pause_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    status: PauseStatus,
+) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)
+        .map_err(|_| ContractError::Unauthorized {})?;
+    PAUSE_STATUS.save(deps.storage, &status)?;
+
+    let event = Event::new("update_pause_status")
+        .add_attribute("sender", info.sender)
+        .add_attribute("painting", status.painting.to_string())
+        .add_attribute("minting", status.minting.to_string())
+        .add_attribute("transfers", status.transfers.to_string());
+    Ok(Response::new().add_event(event))
+}
+
+pub fn execute_update_position_pricing(
+    deps: DepsMut,
+    info: MessageInfo,
+    pricing: Option<PositionPricing>,
+) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)
+        .map_err(|_| ContractError::Unauthorized {})?;
+
+    let event = Event::new("update_position_pricing").add_attribute("sender", info.sender);
+    let event = match pricing {
+        Some(pricing) => {
+            let config = CONFIG.load(deps.storage)?;
+            validate_position_pricing(&config, &pricing)?;
+            POSITION_PRICING.save(deps.storage, &pricing)?;
+            event
+                .add_attribute("edge_weight", pricing.edge_weight.to_string())
+                .add_attribute("corner_weight", pricing.corner_weight.to_string())
+                .add_attribute("zones", pricing.zones.len().to_string())
+        }
+        None => {
+            POSITION_PRICING.remove(deps.storage);
+            event
+        }
+    };
+    Ok(Response::new().add_event(event))
+}
+
+pub fn execute_update_volume_discounts(
+    deps: DepsMut,
+    info: MessageInfo,
+    discounts: Vec<VolumeDiscount>,
+) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)
+        .map_err(|_| ContractError::Unauthorized {})?;
+
+    if discounts.is_empty() {
+        VOLUME_DISCOUNTS.remove(deps.storage);
+    } else {
+        validate_volume_discounts(&discounts)?;
+        VOLUME_DISCOUNTS.save(deps.storage, &discounts)?;
+    }
+
+    let event = Event::new("update_volume_discounts")
+        .add_attribute("sender", info.sender)
+        .add_attribute("tiers", discounts.len().to_string());
+    Ok(Response::new().add_event(event))
+}
+
+pub fn execute_update_fee_split(
+    deps: DepsMut,
+    info: MessageInfo,
+    shares: Vec<FeeShare>,
+) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)
+        .map_err(|_| ContractError::Unauthorized {})?;
+
+    if shares.is_empty() {
+        FEE_SPLIT.remove(deps.storage);
+    } else {
+        validate_fee_split(deps.api, &shares)?;
+        FEE_SPLIT.save(deps.storage, &shares)?;
+    }
+
+    let event = Event::new("update_fee_split")
+        .add_attribute("sender", info.sender)
+        .add_attribute("recipients", shares.len().to_string());
+    Ok(Response::new().add_event(event))
+}
+
+/// Rejects messages whose action the owner has paused
+pub fn assert_not_paused(storage: &dyn Storage, msg: &ExecuteMsg) -> Result<(), ContractError> {
+    let status = PAUSE_STATUS.may_load(storage)?.unwrap_or_default();
+    let action = match msg {
+        ExecuteMsg::SetPixelColor { .. } | ExecuteMsg::SetPixelColors { .. } if status.painting => {
+            "painting"
+        }
+        ExecuteMsg::MintTile { .. } if status.minting => "minting",
+        ExecuteMsg::Base(sg721::ExecuteMsg::TransferNft { .. } | sg721::ExecuteMsg::SendNft { .. })
+            if status.transfers =>
+        {
+            "transfers"
+        }
+        _ => return Ok(()),
+    };
+    Err(ContractError::Paused { action: action.to_string() })
+}
+
+pub fn validate_config(api: &dyn Api, msg: MosaicConfigMsg) -> Result<MosaicConfig, ContractError> {
+    if msg.denom.is_empty() {
+        return Err(ContractError::InvalidConfig("Denom cannot be empty".to_string()));
+    }
+    if msg.developer_royalties > 100 {
+        return Err(ContractError::InvalidConfig(
+            "Developer royalties cannot be greater than 100%".to_string(),
+        ));
+    }
+    // Longer rentals cost more, and no rental is free
+    let tiers = &msg.fee_tiers;
+    if tiers.tier_1h.is_zero() || tiers.tier_1h >= tiers.tier_12h || tiers.tier_12h >= tiers.tier_24h {
+        return Err(ContractError::InvalidConfig(
+            "Fee tiers must be non-zero and strictly increasing".to_string(),
+        ));
+    }
+    if msg.total_tiles == 0 || msg.pixels_per_tile == 0 {
+        return Err(ContractError::InvalidConfig(
+            "Tile dimensions must be greater than zero".to_string(),
+        ));
+    }
+    if msg.total_tiles.checked_mul(msg.pixels_per_tile).is_none() {
+        return Err(ContractError::InvalidConfig("Too many pixels".to_string()));
+    }
+
+    Ok(MosaicConfig {
+        denom: msg.denom,
+        fee_tiers: msg.fee_tiers,
+        developer_address: api.addr_validate(&msg.developer_address)?,
+        developer_royalties: msg.developer_royalties,
+        mint_price: msg.mint_price,
+        total_tiles: msg.total_tiles,
+        pixels_per_tile: msg.pixels_per_tile,
+    })
+}
+
+fn validate_position_pricing(
+    config: &MosaicConfig,
+    pricing: &PositionPricing,
+) -> Result<(), ContractError> {
+    if pricing.tiles_per_row == 0 || pricing.tiles_per_row > config.total_tiles {
+        return Err(ContractError::InvalidConfig(
+            "Tiles per row must be between one and the number of tiles".to_string(),
+        ));
+    }
+    if pricing.zones.len() > MAX_PRICE_ZONES {
+        return Err(ContractError::InvalidConfig(format!(
+            "Cannot have more than {MAX_PRICE_ZONES} price zones"
+        )));
+    }
+    if pricing.zones.iter().any(|zone| zone.width == 0 || zone.height == 0) {
+        return Err(ContractError::InvalidConfig("Price zones cannot be empty".to_string()));
+    }
+    let weights = [pricing.edge_weight, pricing.corner_weight];
+    if weights.into_iter().chain(pricing.zones.iter().map(|zone| zone.weight)).any(|weight| weight == 0) {
+        return Err(ContractError::InvalidConfig(
+            "Price weights must be greater than zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_volume_discounts(discounts: &[VolumeDiscount]) -> Result<(), ContractError> {
+    if discounts.len() > MAX_VOLUME_DISCOUNTS {
+        return Err(ContractError::InvalidConfig(format!(
+            "Cannot have more than {MAX_VOLUME_DISCOUNTS} volume discount tiers"
+        )));
+    }
+    if discounts.iter().any(|tier| tier.min_pixels < 2 || tier.discount >= fees::WEIGHT_BASE) {
+        return Err(ContractError::InvalidConfig(
+            "Volume discounts need at least two pixels and must be below 100%".to_string(),
+        ));
+    }
+    // Larger batches always get a larger discount
+    if discounts
+        .windows(2)
+        .any(|pair| pair[0].min_pixels >= pair[1].min_pixels || pair[0].discount >= pair[1].discount)
+    {
+        return Err(ContractError::InvalidConfig(
+            "Volume discount tiers must grow in size and discount".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_fee_split(api: &dyn Api, shares: &[FeeShare]) -> Result<(), ContractError> {
+    if shares.len() > MAX_FEE_SHARES {
+        return Err(ContractError::InvalidConfig(format!(
+            "Cannot have more than {MAX_FEE_SHARES} fee recipients"
+        )));
+    }
+    if shares.iter().any(|share| share.weight == 0) {
+        return Err(ContractError::InvalidConfig(
+            "Fee share weights must be greater than zero".to_string(),
+        ));
+    }
+    let total: u64 = shares.iter().map(|share| share.weight as u64).sum();
+    if total != fees::WEIGHT_BASE as u64 {
+        return Err(ContractError::InvalidConfig(
+            "Fee share weights must sum to 100%".to_string(),
+        ));
+    }
+    for (index, share) in shares.iter().enumerate() {
+        if let FeeRecipient::Address(address) = &share.recipient {
+            api.addr_validate(address.as_str())?;
+        }
+        if shares[..index].iter().any(|other| other.recipient == share.recipient) {
+            return Err(ContractError::InvalidConfig("Duplicate fee recipient".to_string()));
+        }
+    }
+    Ok(())
+}
+
+pub fn query_tile_hash(deps: Deps, tile_id: u32) -> StdResult<TileHashResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(TileHashResponse {
+        tile_id,
+        hash: load_tile_hash(deps.storage, &config, tile_id)?,
+    })
+}
+
+pub fn query_estimate_fee(
+    deps: Deps,
+    env: Env,
+    updates: Vec<PixelUpdate>,
+) -> Result<FeeEstimateResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pricing = POSITION_PRICING.may_load(deps.storage)?;
+    let discounts = VOLUME_DISCOUNTS.may_load(deps.storage)?.unwrap_or_default();
+    let discount = fees::volume_discount(&discounts, updates.len());
+
+    let now = env.block.time.seconds();
+    let mut total_fee = Uint128::zero();
+    let mut pixel_fees = vec![];
+    for update in &updates {
+        let tile_id = update.pixel_id / config.pixels_per_tile;
+        if tile_id >= config.total_tiles {
+            return Err(ContractError::PixelOutOfRange {});
+        }
+        let duration = update.expiration.saturating_sub(now);
+        let fee = fees::calculate_position_fee(&config, pricing.as_ref(), tile_id, duration)?;
+        let amount = fees::apply_discount(fee.amount, discount);
+        total_fee = total_fee.checked_add(amount).map_err(StdError::from)?;
+        pixel_fees.push(amount);
+    }
+
+    Ok(FeeEstimateResponse {
+        fee: Coin {
+            denom: config.denom,
+            amount: total_fee,
+        },
+        pixel_fees,
+        discount,
+    })
+}
+
+pub fn query_earnings(deps: Deps, address: String) -> StdResult<EarningsResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let earnings = EARNINGS.may_load(deps.storage, &addr)?.unwrap_or_default();
+    Ok(EarningsResponse { address, earnings })
+}
+
+/// Returns the stored hash of a tile, falling back to the hash of a default tile
+fn load_tile_hash(storage: &dyn Storage, config: &MosaicConfig, tile_id: u32) -> StdResult<HexBinary> {
+    Ok(TILE_HASHES
+        .may_load(storage, tile_id)?
+        .unwrap_or_else(|| TileMetadata::new(tile_id, config.pixels_per_tile).calculate_hash().into()))
+}
+
+fn verify_tile_hash(
+    storage: &dyn Storage,
+    config: &MosaicConfig,
+    tile_metadata: &TileMetadata,
+) -> Result<(), ContractError> {
+    let expected = load_tile_hash(storage, config, tile_metadata.tile_id)?;
+    if tile_metadata.calculate_hash() != expected {
+        return Err(ContractError::TileHashMismatch {
+            tile_id: tile_metadata.tile_id,
+        });
+    }
+    Ok(())
+}
+
+/// Commits to the tile metadata by its hash and keeps the NFT extension of
+/// minted tiles in sync, so `nft_info` always returns the current metadata
+pub(crate) fn save_tile_metadata(storage: &mut dyn Storage, tile_metadata: &TileMetadata) -> StdResult<HexBinary> {
+    let tile_hash = HexBinary::from(tile_metadata.calculate_hash());
+    TILE_HASHES.save(storage, tile_metadata.tile_id, &tile_hash)?;
+
+    let tokens = sg721_base::Sg721Contract::<Extension>::default().parent.tokens;
+    let token_id = tile_metadata.tile_id.to_string();
+    if let Some(mut token) = tokens.may_load(storage, &token_id)? {
+        token.extension.tile_metadata = tile_metadata.to_bytes();
+        tokens.save(storage, &token_id, &token)?;
+    }
+    Ok(tile_hash)
+}
+
+fn assert_payment(info: &MessageInfo, required_fee: &Coin) -> Result<(), ContractError> {
+    if info.funds.len() != 1 || info.funds[0] != *required_fee {
         return Err(ContractError::InvalidFee {
-            expected: required_fee,
-            received: info.funds.get(0).cloned(),
+            expected: required_fee.clone(),
+            received: info.funds.first().cloned(),
         });
     }
+    Ok(())
+}
 
-    // Parse the current tile metadata
-    let tile_metadata = TileMetadata::from_bytes(&current_tile_metadata)
-        .map_err(|e| ContractError::InvalidPixelUpdate(e.to_string()))?;
+fn parse_tile_metadata(data: &[u8]) -> Result<TileMetadata, ContractError> {
+    TileMetadata::from_bytes(data).map_err(|e| ContractError::InvalidPixelUpdate(e.to_string()))
+}
 
+/// Checks that the pixel belongs to the tile and is not locked, then applies the update
+fn apply_pixel_update(
+    env: &Env,
+    tile_metadata: &mut TileMetadata,
+    pixel_update: &PixelUpdate,
+) -> Result<(), ContractError> {
     // Verify pixel is in tile range
-    if !crate::types::is_pixel_in_tile(pixel_update.pixel_id, tile_metadata.tile_id) {
+    if !crate::types::is_pixel_in_tile(
+        pixel_update.pixel_id,
+        tile_metadata.tile_id,
+        tile_metadata.pixels_per_tile(),
+    ) {
         return Err(ContractError::PixelOutOfRange {});
     }
 
     // Get current pixel metadata
-    let current_pixel = tile_metadata.get_pixel(pixel_update.pixel_id)
-        .map_err(|e| ContractError::InvalidPixelUpdate(e))?;
+    let current_pixel = tile_metadata.get_pixel(pixel_update.pixel_id)?;
 
     // Verify pixel is not expired
-    if current_pixel.expiration > env.block.time.seconds() {
+    if !current_pixel.is_available(env.block.time.seconds()) {
         return Err(ContractError::InvalidPixelUpdate("Pixel is currently locked".to_string()));
     }
 
-    // Get the owner of the tile (NFT)
-    let owner_response: OwnerOfResponse = deps.querier.query_wasm_smart(
-        info.sender.clone(),
-        &cw721::Cw721QueryMsg::OwnerOf {
-            token_id: tile_metadata.tile_id.to_string(),
-            include_expired: None,
-        },
+    tile_metadata.update_pixel(
+        pixel_update.pixel_id,
+        pixel_update.color,
+        pixel_update.expiration,
+        env.block.time.seconds(),
     )?;
+    Ok(())
+}
 
-    // Calculate fee distribution
-    let developer_royalties = fees::developer_royalties() as u128;
-    let developer_amount = required_fee.amount.multiply_ratio(developer_royalties, 100u128);
-    let owner_amount = required_fee.amount - developer_amount;
+/// Builds the `pixel_update` event of a pixel that was just painted
+fn pixel_event(tile_metadata: &TileMetadata, pixel_id: u32) -> Result<Event, ContractError> {
+    let metadata = tile_metadata
+        .get_pixel(pixel_id)?
+        .metadata()
+        .ok_or_else(|| ContractError::InvalidPixelUpdate("Pixel has not been painted".to_string()))?;
+    pixel_update_event(PixelId::Tile(pixel_id), &metadata)
+}
 
-    // Create bank messages for fee distribution
-    let mut messages: Vec<BankMsg> = vec![];
-
-    // Send developer fee
-    messages.push(BankMsg::Send {
-        to_address: fees::developer_address(),
-        amount: vec![Coin {
-            denom: required_fee.denom.clone(),
-            amount: developer_amount,
-        }],
-    });
-
-    // Send remaining fee to tile owner
-    messages.push(BankMsg::Send {
-        to_address: owner_response.owner,
-        amount: vec![Coin {
-            denom: required_fee.denom,
-            amount: owner_amount,
-        }],
-    });
-
-    // Return response with updated metadata and bank messages
-    Ok(Response::new()
-        .add_messages(messages)
-        .add_attribute("action", "set_pixel_color")
-        .add_attribute("pixel_id", pixel_update.pixel_id.to_string())
-        .add_attribute("color", format!("{:?}", pixel_update.color))
-        .add_attribute("expiration", pixel_update.expiration.to_string())
-        .add_attribute("duration", duration.to_string())
-        .add_attribute("fee", required_fee.amount.to_string())
-        .add_attribute("developer_fee", developer_amount)
-        .add_attribute("owner_fee", owner_amount))
-} 
\ No newline at end of file
+/// Holder of the tile NFT, who collects the owner share of rentals
+fn tile_owner(storage: &dyn Storage, tile_id: u32) -> Result<Addr, ContractError> {
+    sg721_base::Sg721Contract::<Extension>::default()
+        .parent
+        .tokens
+        .may_load(storage, &tile_id.to_string())?
+        .map(|token| token.owner)
+        .ok_or(ContractError::TileNotMinted { tile_id })
+}
+
+/// Attributes reporting the fee credited to each recipient
+fn recipient_fee_attributes(recipient_amounts: &BTreeMap<Addr, Uint128>) -> Vec<(String, String)> {
+    recipient_amounts
+        .iter()
+        .map(|(recipient, amount)| (format!("fee_{recipient}"), amount.to_string()))
+        .collect()
+}
+
+/// Recipient and share of the royalty taken from rental fees. The collection
+/// royalty info governs rentals when set, the developer royalties otherwise.
+fn rental_royalty(storage: &dyn Storage, config: &MosaicConfig) -> StdResult<(Addr, Decimal)> {
+    let base = sg721_base::Sg721Contract::<Extension>::default();
+    Ok(match base.collection_info.load(storage)?.royalty_info {
+        Some(royalty_info) => (royalty_info.payment_address, royalty_info.share),
+        None => (
+            config.developer_address.clone(),
+            Decimal::percent(config.developer_royalties as u64),
+        ),
+    })
+}
+
+/// Credits the royalty share and the remaining share of each recipient to
+/// the earnings ledger, to be collected later with `Withdraw`
+fn credit_earnings(
+    storage: &mut dyn Storage,
+    denom: &str,
+    royalty: (Addr, Uint128),
+    recipient_amounts: BTreeMap<Addr, Uint128>,
+) -> StdResult<()> {
+    let credits = std::iter::once(royalty)
+        .chain(recipient_amounts)
+        .filter(|(_, amount)| !amount.is_zero());
+    for (recipient, amount) in credits {
+        EARNINGS.update(storage, &recipient, |earnings| -> StdResult<_> {
+            let mut earnings = earnings.unwrap_or_default();
+            match earnings.iter_mut().find(|coin| coin.denom == denom) {
+                Some(coin) => coin.amount = coin.amount.checked_add(amount)?,
+                None => earnings.push(Coin {
+                    denom: denom.to_string(),
+                    amount,
+                }),
+            }
+            Ok(earnings)
+        })?;
+    }
+    Ok(())
+}
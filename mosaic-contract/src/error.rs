@@ -27,11 +27,38 @@ pub enum ContractError {
     #[error("Invalid expiration")]
     InvalidExpiration {},
 
+    #[error("Contract is paused for {action}")]
+    Paused { action: String },
+
+    #[error("No earnings to withdraw")]
+    NoEarnings {},
+
+    #[error("Insufficient earnings. Requested {requested}, available {available}")]
+    InsufficientEarnings { requested: Coin, available: Coin },
+
+    #[error("Invalid tile ID")]
+    InvalidTileId {},
+
+    #[error("Tile {tile_id} is already minted")]
+    TileAlreadyMinted { tile_id: u32 },
+
+    #[error("Tile {tile_id} has not been minted")]
+    TileNotMinted { tile_id: u32 },
+
+    #[error("Tile metadata does not match the stored hash for tile {tile_id}")]
+    TileHashMismatch { tile_id: u32 },
+
     #[error("Base contract error: {0}")]
     Base(String),
 
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+
     #[error("Feature disabled: {feature}")]
     FeatureDisabled { feature: String },
+
+    #[error("Error while migrating: {0}")]
+    MigrationError(String),
 }
 
 impl PartialEq for ContractError {
@@ -47,9 +74,30 @@ impl PartialEq for ContractError {
                 a_exp == b_exp && a_rec == b_rec
             },
             (ContractError::InvalidExpiration {}, ContractError::InvalidExpiration {}) => true,
+            (ContractError::Paused { action: a }, ContractError::Paused { action: b }) => a == b,
+            (ContractError::NoEarnings {}, ContractError::NoEarnings {}) => true,
+            (
+                ContractError::InsufficientEarnings { requested: r1, available: a1 },
+                ContractError::InsufficientEarnings { requested: r2, available: a2 },
+            ) => r1 == r2 && a1 == a2,
+            (ContractError::InvalidTileId {}, ContractError::InvalidTileId {}) => true,
+            (ContractError::TileAlreadyMinted { tile_id: a }, ContractError::TileAlreadyMinted { tile_id: b }) => a == b,
+            (ContractError::TileNotMinted { tile_id: a }, ContractError::TileNotMinted { tile_id: b }) => a == b,
+            (ContractError::TileHashMismatch { tile_id: a }, ContractError::TileHashMismatch { tile_id: b }) => a == b,
             (ContractError::Base(a), ContractError::Base(b)) => a == b,
+            (ContractError::InvalidConfig(a), ContractError::InvalidConfig(b)) => a == b,
             (ContractError::FeatureDisabled { feature: a }, ContractError::FeatureDisabled { feature: b }) => a == b,
+            (ContractError::MigrationError(a), ContractError::MigrationError(b)) => a == b,
             _ => false,
         }
     }
 }
+
+impl From<mosaic_types::Error> for ContractError {
+    fn from(err: mosaic_types::Error) -> Self {
+        match err {
+            mosaic_types::Error::PixelOutOfRange => ContractError::PixelOutOfRange {},
+            err => ContractError::InvalidPixelUpdate(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use cosmwasm_std::Event;
+
+use crate::error::ContractError;
+
+pub use mosaic_types::{Color, PixelId, PixelMetadata, EVENT_TYPE_PIXEL_UPDATE, PIXEL_UPDATE_EVENT_VERSION};
+
+/// Builds the `pixel_update` event announcing a new version of a pixel
+pub fn pixel_update_event(pixel_id: PixelId, metadata: &PixelMetadata) -> Result<Event, ContractError> {
+    let attributes = mosaic_types::pixel_update_attributes(pixel_id, metadata)?;
+    Ok(Event::new(EVENT_TYPE_PIXEL_UPDATE).add_attributes(attributes))
+}
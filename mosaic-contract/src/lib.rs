@@ -1,37 +1,39 @@
 pub mod contract;
 pub mod error;
+pub mod events;
+pub mod migrations;
 pub mod msg;
+pub mod state;
 pub mod types;
 pub mod constants;
 
-use cosmwasm_std::{entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, from_json};
-use sg721_base::{self, msg::QueryMsg};
-use sg721::InstantiateMsg;
+use cosmwasm_std::{entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, from_json};
+use sg721_base::{self, msg::QueryMsg as BaseQueryMsg};
 use cw721_base::MinterResponse;
 use sg_std::StargazeMsgWrapper;
 
 use crate::{
     error::ContractError,
-    msg::ExecuteMsg,
-    contract::execute_set_pixel_color,
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg},
+    contract::{
+        assert_not_paused, execute_mint_tile, execute_set_pixel_color, execute_set_pixel_colors,
+        execute_update_config, execute_update_fee_split, execute_update_pause_status,
+        execute_update_position_pricing, execute_update_volume_discounts, execute_withdraw,
+        query_earnings, query_estimate_fee, query_tile_hash, validate_config,
+    },
+    state::{CONFIG, FEE_SPLIT, PAUSE_STATUS, POSITION_PRICING, VOLUME_DISCOUNTS},
     types::Extension,
 };
 
 // Version info for migration
-const CONTRACT_NAME: &str = "crates.io:mosaic-nft";
+const CONTRACT_NAME: &str = "crates.io:mosaic-tiles";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+#[derive(Default)]
 pub struct MosaicContract<'a> {
     pub base: sg721_base::Sg721Contract<'a, Extension>,
 }
 
-impl<'a> Default for MosaicContract<'a> {
-    fn default() -> Self {
-        Self {
-            base: sg721_base::Sg721Contract::default(),
-        }
-    }
-}
 
 impl<'a> MosaicContract<'a> {
     pub fn instantiate(
@@ -43,9 +45,13 @@ impl<'a> MosaicContract<'a> {
     ) -> Result<Response<StargazeMsgWrapper>, ContractError> {
         // Set contract version first
         cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-        
+
+        // Validate and store mosaic configuration
+        let config = validate_config(deps.api, msg.config)?;
+        CONFIG.save(deps.storage, &config)?;
+
         // Initialize base contract
-        let res = self.base.instantiate(deps, env, info, msg)
+        let res = self.base.instantiate(deps, env, info, msg.base)
             .map_err(|e| ContractError::Base(e.to_string()))?;
 
         Ok(Response::new()
@@ -60,6 +66,8 @@ impl<'a> MosaicContract<'a> {
         info: MessageInfo,
         msg: ExecuteMsg,
     ) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+        assert_not_paused(deps.storage, &msg)?;
+
         match msg {
             // Forward all base NFT functionality to base contract
             ExecuteMsg::Base(base_msg) => {
@@ -67,24 +75,49 @@ impl<'a> MosaicContract<'a> {
                     sg721::ExecuteMsg::Burn { .. } => {
                         Err(ContractError::FeatureDisabled { feature: "burn".to_string() })
                     }
+                    // Tiles are only minted through MintTile, which checks the position,
+                    // price and duplicates
+                    sg721::ExecuteMsg::Mint { .. } => {
+                        Err(ContractError::FeatureDisabled { feature: "mint".to_string() })
+                    }
                     _ => self.base.execute(deps, env, info, base_msg)
                         .map_err(|e| ContractError::Base(e.to_string()))
                 }
             }
             // Custom pixel color functionality
             ExecuteMsg::SetPixelColor { current_tile_metadata, pixel_update } => {
-                // Verify sender is the minter
-                let minter: MinterResponse = from_json(
-                    &self.base.query(deps.as_ref(), env.clone(), QueryMsg::Minter {})
-                        .map_err(|e| ContractError::Std(StdError::generic_err(e.to_string())))?
-                )?;
-                
-                if info.sender.to_string() != minter.minter.unwrap_or_default() {
-                    return Err(ContractError::Unauthorized {});
-                }
+                self.assert_minter(deps.as_ref(), &env, &info)?;
                 execute_set_pixel_color(deps, env, info, current_tile_metadata, pixel_update)
             }
+            ExecuteMsg::SetPixelColors { current_tiles_metadata, updates } => {
+                self.assert_minter(deps.as_ref(), &env, &info)?;
+                execute_set_pixel_colors(deps, env, info, current_tiles_metadata, updates)
+            }
+            ExecuteMsg::MintTile { tile_id } => execute_mint_tile(deps, info, tile_id),
+            ExecuteMsg::Withdraw { amount } => execute_withdraw(deps, info, amount),
+            ExecuteMsg::UpdateConfig { config } => execute_update_config(deps, info, config),
+            ExecuteMsg::UpdatePauseStatus { status } => execute_update_pause_status(deps, info, status),
+            ExecuteMsg::UpdatePositionPricing { pricing } => {
+                execute_update_position_pricing(deps, info, pricing)
+            }
+            ExecuteMsg::UpdateVolumeDiscounts { discounts } => {
+                execute_update_volume_discounts(deps, info, discounts)
+            }
+            ExecuteMsg::UpdateFeeSplit { shares } => execute_update_fee_split(deps, info, shares),
+        }
+    }
+
+    fn assert_minter(&self, deps: Deps, env: &Env, info: &MessageInfo) -> Result<(), ContractError> {
+        // Verify sender is the minter
+        let minter: MinterResponse = from_json(
+            self.base.query(deps, env.clone(), BaseQueryMsg::Minter {})
+                .map_err(|e| ContractError::Std(StdError::generic_err(e.to_string())))?
+        )?;
+
+        if info.sender != minter.minter.unwrap_or_default() {
+            return Err(ContractError::Unauthorized {});
         }
+        Ok(())
     }
 
     pub fn query(
@@ -93,9 +126,29 @@ impl<'a> MosaicContract<'a> {
         env: Env,
         msg: QueryMsg,
     ) -> Result<Binary, ContractError> {
-        // Forward all queries to base contract
-        self.base.query(deps, env, msg)
-            .map_err(|e| ContractError::Std(StdError::generic_err(e.to_string())))
+        match msg {
+            // Forward all base queries to base contract
+            QueryMsg::Base(base_msg) => self.base.query(deps, env, base_msg)
+                .map_err(|e| ContractError::Std(StdError::generic_err(e.to_string()))),
+            QueryMsg::TileHash { tile_id } => Ok(to_json_binary(&query_tile_hash(deps, tile_id)?)?),
+            QueryMsg::Config {} => Ok(to_json_binary(&CONFIG.load(deps.storage)?)?),
+            QueryMsg::Earnings { address } => Ok(to_json_binary(&query_earnings(deps, address)?)?),
+            QueryMsg::PauseStatus {} => {
+                Ok(to_json_binary(&PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default())?)
+            }
+            QueryMsg::PositionPricing {} => {
+                Ok(to_json_binary(&POSITION_PRICING.may_load(deps.storage)?)?)
+            }
+            QueryMsg::VolumeDiscounts {} => {
+                Ok(to_json_binary(&VOLUME_DISCOUNTS.may_load(deps.storage)?.unwrap_or_default())?)
+            }
+            QueryMsg::FeeSplit {} => {
+                Ok(to_json_binary(&FEE_SPLIT.may_load(deps.storage)?.unwrap_or_default())?)
+            }
+            QueryMsg::EstimateFee { updates } => {
+                Ok(to_json_binary(&query_estimate_fee(deps, env, updates)?)?)
+            }
+        }
     }
 }
 
@@ -131,5 +184,14 @@ pub fn query(
     contract.query(deps, env, msg)
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg,
+) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    migrations::migrate(deps, env, msg)
+}
+
 #[cfg(test)]
 mod tests;
\ No newline at end of file
@@ -0,0 +1,127 @@
+use cosmwasm_std::{DepsMut, Env, Response};
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+use sg_std::StargazeMsgWrapper;
+
+use crate::{error::ContractError, msg::MigrateMsg, CONTRACT_NAME, CONTRACT_VERSION};
+
+/// cw2 name and version of the contracts released before they had names of their own
+const LEGACY_CONTRACT_NAME: &str = "crates.io:mosaic-nft";
+const LEGACY_CONTRACT_VERSION: &str = "0.1.0";
+
+/// A state migration, run when upgrading from a version older than the one it is registered for
+type MigrationStep = fn(DepsMut, &MigrateMsg) -> Result<(), ContractError>;
+
+/// Migration steps in ascending version order
+const MIGRATIONS: &[(&str, MigrationStep)] = &[("0.2.0", v0_2_0::migrate)];
+
+/// Checks the stored cw2 info, refuses downgrades and runs every step newer than the stored version
+pub fn migrate(
+    mut deps: DepsMut,
+    _env: Env,
+    msg: MigrateMsg,
+) -> Result<Response<StargazeMsgWrapper>, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    // Both contracts stored the same name up to 0.1.0
+    let legacy =
+        stored.contract == LEGACY_CONTRACT_NAME && stored.version == LEGACY_CONTRACT_VERSION;
+    if stored.contract != CONTRACT_NAME && !legacy {
+        return Err(ContractError::MigrationError(format!(
+            "cannot migrate from contract {}",
+            stored.contract
+        )));
+    }
+
+    let from = parse_version(&stored.version)?;
+    let to = parse_version(CONTRACT_VERSION)?;
+    if from > to {
+        return Err(ContractError::MigrationError(format!(
+            "cannot downgrade from {from} to {to}"
+        )));
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", from.to_string())
+        .add_attribute("to_version", to.to_string());
+
+    for (version, step) in MIGRATIONS {
+        let version = parse_version(version)?;
+        if from < version && version <= to {
+            step(deps.branch(), &msg)?;
+            res = res.add_attribute("migration", version.to_string());
+        }
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(res)
+}
+
+fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version)
+        .map_err(|err| ContractError::MigrationError(format!("invalid version {version}: {err}")))
+}
+
+/// 0.1.0 had no configuration or tile hashes and encoded pixels as `{color, expiration, pixel_id}`
+mod v0_2_0 {
+    use cosmwasm_std::{Order, StdResult};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{
+        contract::{save_tile_metadata, validate_config},
+        state::CONFIG,
+        types::{Extension, Pixel, TileMetadata},
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct LegacyPixel {
+        pub color: [u8; 3],
+        pub expiration: u64,
+        pub pixel_id: u32,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct LegacyTile {
+        pub tile_id: u32,
+        pub pixels: Vec<LegacyPixel>,
+    }
+
+    pub fn migrate(deps: DepsMut, msg: &MigrateMsg) -> Result<(), ContractError> {
+        if CONFIG.may_load(deps.storage)?.is_none() {
+            let config = msg.config.clone().ok_or_else(|| {
+                ContractError::MigrationError("config is required to migrate from 0.1.0".to_string())
+            })?;
+            let config = validate_config(deps.api, config)?;
+            CONFIG.save(deps.storage, &config)?;
+        }
+
+        // Re-encode the metadata of every minted tile and record its hash
+        let tokens = sg721_base::Sg721Contract::<Extension>::default().parent.tokens;
+        let minted = tokens
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (token_id, token) in minted {
+            let legacy: LegacyTile = bincode::deserialize(&token.extension.tile_metadata)
+                .map_err(|err| {
+                    ContractError::MigrationError(format!("invalid metadata of tile {token_id}: {err}"))
+                })?;
+            let tile_metadata = TileMetadata {
+                tile_id: legacy.tile_id,
+                pixels: legacy
+                    .pixels
+                    .into_iter()
+                    .map(|pixel| Pixel {
+                        color: pixel.color,
+                        expiration: pixel.expiration,
+                        ..Pixel::default()
+                    })
+                    .collect(),
+            };
+            save_tile_metadata(deps.storage, &tile_metadata)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,8 +1,63 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sg721::ExecuteMsg as Sg721ExecuteMsg;
-use cosmwasm_std::Empty;
-use crate::types::Extension;
+use sg721::{ExecuteMsg as Sg721ExecuteMsg, InstantiateMsg as Sg721InstantiateMsg};
+use sg721_base::msg::QueryMsg as Sg721QueryMsg;
+use cosmwasm_std::{Coin, Empty, HexBinary, Uint128};
+use crate::{
+    state::{FeeShare, FeeTiers, PositionPricing, VolumeDiscount},
+    types::Extension,
+};
+
+/// Instantiate message
+/// The sg721-base collection is instantiated from `base`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Standard sg721 instantiation
+    pub base: Sg721InstantiateMsg,
+    /// Mosaic configuration
+    pub config: MosaicConfigMsg,
+}
+
+/// Unchecked version of `MosaicConfig`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MosaicConfigMsg {
+    pub denom: String,
+    pub fee_tiers: FeeTiers,
+    pub developer_address: String,
+    pub developer_royalties: u8,
+    pub mint_price: Uint128,
+    pub total_tiles: u32,
+    pub pixels_per_tile: u32,
+}
+
+/// Migrate message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct MigrateMsg {
+    /// Mosaic configuration, required when migrating from a version without one
+    pub config: Option<MosaicConfigMsg>,
+}
+
+/// Configuration fields the owner can change after instantiation
+/// Tile dimensions are fixed since stored tile hashes depend on them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct UpdateConfigMsg {
+    pub denom: Option<String>,
+    pub fee_tiers: Option<FeeTiers>,
+    pub developer_address: Option<String>,
+    pub developer_royalties: Option<u8>,
+    pub mint_price: Option<Uint128>,
+}
+
+/// Actions halted by the owner, e.g. during an incident
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseStatus {
+    /// Halts SetPixelColor and SetPixelColors
+    pub painting: bool,
+    /// Halts MintTile
+    pub minting: bool,
+    /// Halts transferring and sending tiles
+    pub transfers: bool,
+}
 
 /// Pixel update information
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -36,9 +91,99 @@ pub enum ExecuteMsg {
         /// The pixel update to apply
         pixel_update: PixelUpdate,
     },
+
+    /// Custom extension: Set the color of several pixels at once
+    /// Every tile touched by the updates is verified once, the fees of all
+    /// updates are paid in a single payment and the whole batch fails if any
+    /// pixel is locked
+    SetPixelColors {
+        /// Current metadata of every tile touched by the updates
+        current_tiles_metadata: Vec<Vec<u8>>,
+        /// The pixel updates to apply
+        updates: Vec<PixelUpdate>,
+    },
+
+    /// Mint the tile at `tile_id` to the sender for the configured mint price
+    /// Proceeds are split between the developer and the collection creator
+    MintTile { tile_id: u32 },
+
+    /// Withdraw accrued fees and mint proceeds to the sender
+    /// Withdraws every balance when `amount` is not set
+    Withdraw { amount: Option<Coin> },
+
+    /// Update the contract configuration, only callable by the owner
+    UpdateConfig { config: UpdateConfigMsg },
+
+    /// Pause or resume painting, minting and transfers, only callable by the owner
+    UpdatePauseStatus { status: PauseStatus },
+
+    /// Set or remove position based rental pricing, only callable by the owner
+    UpdatePositionPricing { pricing: Option<PositionPricing> },
+
+    /// Replace the volume discount tiers of batched paints, only callable by the owner
+    /// Tiers must be ordered by `min_pixels` with growing discounts, empty to remove them
+    UpdateVolumeDiscounts { discounts: Vec<VolumeDiscount> },
+
+    /// Replace the split of rental fees left after royalties, only callable by the owner
+    /// Weights must sum to 100%, empty to pay everything to the tile owner
+    UpdateFeeSplit { shares: Vec<FeeShare> },
 }
 
-// Re-export base messages
-pub use sg721::InstantiateMsg;
-pub use sg721_base::msg::QueryMsg;
-  
\ No newline at end of file
+/// Query messages
+/// Standard sg721-base queries are forwarded through the Base variant
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Standard sg721-base NFT queries
+    Base(Sg721QueryMsg),
+
+    /// Hash of the latest metadata of a tile, returns TileHashResponse
+    TileHash { tile_id: u32 },
+
+    /// Contract configuration, returns MosaicConfig
+    Config {},
+
+    /// Fees and mint proceeds an address can withdraw, returns EarningsResponse
+    Earnings { address: String },
+
+    /// Actions paused by the owner, returns PauseStatus
+    PauseStatus {},
+
+    /// Position based rental pricing, returns Option<PositionPricing>
+    PositionPricing {},
+
+    /// Volume discount tiers of batched paints, returns Vec<VolumeDiscount>
+    VolumeDiscounts {},
+
+    /// Split of rental fees left after royalties, returns Vec<FeeShare>
+    FeeSplit {},
+
+    /// Fee of painting `updates` at the current block time, returns FeeEstimateResponse
+    EstimateFee { updates: Vec<PixelUpdate> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TileHashResponse {
+    pub tile_id: u32,
+    /// SHA-256 of the tile metadata, see `TileMetadata::calculate_hash`
+    pub hash: HexBinary,
+}
+
+
+  
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeEstimateResponse {
+    /// Total fee to send with the paint message
+    pub fee: Coin,
+    /// Fee of each update, in the order given
+    pub pixel_fees: Vec<Uint128>,
+    /// Volume discount applied to every update, in basis points
+    pub discount: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarningsResponse {
+    pub address: String,
+    pub earnings: Vec<Coin>,
+}
@@ -0,0 +1,112 @@
+use cosmwasm_std::{Addr, Coin, HexBinary, Uint128};
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::msg::PauseStatus;
+
+/// Hash of the latest metadata of each tile, keyed by tile ID.
+/// Tiles without an entry still hold the default `TileMetadata::new` content.
+pub const TILE_HASHES: Map<u32, HexBinary> = Map::new("tile_hashes");
+
+/// Actions paused by the owner, nothing is paused when absent
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
+
+/// Fees and mint proceeds credited to each address and not yet withdrawn
+pub const EARNINGS: Map<&Addr, Vec<Coin>> = Map::new("earnings");
+
+/// Rental fee of each duration tier
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeTiers {
+    /// Fee for durations under one hour
+    pub tier_1h: Uint128,
+    /// Fee for durations under twelve hours
+    pub tier_12h: Uint128,
+    /// Fee for durations under a day, scaled quadratically for longer durations
+    pub tier_24h: Uint128,
+}
+
+/// Rectangle of tiles, in tile coordinates, with its own rental price weight
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceZone {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Price weight of the zone in basis points
+    pub weight: u32,
+}
+
+impl PriceZone {
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x - self.x < self.width && y >= self.y && y - self.y < self.height
+    }
+}
+
+/// Rental price weights by tile position, in basis points of the duration fee
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PositionPricing {
+    /// Number of tiles in each row of the mosaic
+    pub tiles_per_row: u32,
+    /// Weight of tiles on the edge of the mosaic
+    pub edge_weight: u32,
+    /// Weight of the four corner tiles
+    pub corner_weight: u32,
+    /// Zones such as prime locations, the first zone containing a tile sets its weight
+    pub zones: Vec<PriceZone>,
+}
+
+/// Position based rental pricing, every tile is priced alike when absent
+pub const POSITION_PRICING: Item<PositionPricing> = Item::new("position_pricing");
+
+/// Discount on every pixel of a batch painting at least `min_pixels` pixels
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VolumeDiscount {
+    pub min_pixels: u32,
+    /// Discount in basis points
+    pub discount: u32,
+}
+
+/// Volume discount tiers ordered by `min_pixels`, no discount when absent
+pub const VOLUME_DISCOUNTS: Item<Vec<VolumeDiscount>> = Item::new("volume_discounts");
+
+/// Receiver of a share of rental fees
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeRecipient {
+    /// Owner of the tile the fee was paid for
+    TileOwner,
+    Address(Addr),
+}
+
+/// Weighted share of rental fees
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeShare {
+    pub recipient: FeeRecipient,
+    /// Share in basis points, the weights of all shares sum to 100%
+    pub weight: u32,
+}
+
+/// Split of rental fees left after royalties, all to the tile owner when absent
+pub const FEE_SPLIT: Item<Vec<FeeShare>> = Item::new("fee_split");
+
+/// Contract configuration set at instantiation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MosaicConfig {
+    /// Denom of every fee
+    pub denom: String,
+    /// Rental fee tiers
+    pub fee_tiers: FeeTiers,
+    /// Address receiving the developer share of rental fees
+    pub developer_address: Addr,
+    /// Developer share of rental fees and mint proceeds, in percent
+    pub developer_royalties: u8,
+    /// Price of minting a tile
+    pub mint_price: Uint128,
+    /// Number of tiles in the mosaic
+    pub total_tiles: u32,
+    /// Number of pixels in each tile
+    pub pixels_per_tile: u32,
+}
+
+pub const CONFIG: Item<MosaicConfig> = Item::new("config");
@@ -1,65 +1,108 @@
 use cosmwasm_std::{
     testing::{MockApi, MockQuerier, MockStorage},
     testing::{mock_dependencies, mock_env, mock_info},
-    Binary, OwnedDeps, SystemResult, ContractResult, coins, BankMsg, to_json_binary, Empty, from_json, Uint128, Coin,
+    Binary, OwnedDeps, SystemError, SystemResult, ContractResult, WasmQuery, coins, BankMsg, from_json, Uint128, Coin,
+    Addr, Decimal, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
 };
+use cw721::NftInfoResponse;
+use cw721_base::state::TokenInfo;
+use cw_multi_test::{custom_app, BasicApp, ContractWrapper, Executor};
+use serde::{Deserialize, Serialize};
+use sg721_base::msg::QueryMsg as BaseQueryMsg;
+use sg_std::StargazeMsgWrapper;
 
 use crate::{
-    execute, instantiate,
-    msg::{ExecuteMsg, PixelUpdate},
+    execute, instantiate, migrate, query,
+    msg::{
+        EarningsResponse, ExecuteMsg, FeeEstimateResponse, MigrateMsg, PauseStatus, PixelUpdate,
+        QueryMsg, TileHashResponse,
+    },
     error::ContractError,
     constants::fees,
-    types::TileMetadata,
+    events::{Color, PixelMetadata, EVENT_TYPE_PIXEL_UPDATE},
+    types::{Extension, TileMetadata},
+};
+use crate::{
+    contract::validate_config,
+    msg::{InstantiateMsg, MosaicConfigMsg, UpdateConfigMsg},
+    state::{
+        FeeRecipient, FeeShare, FeeTiers, MosaicConfig, PositionPricing, PriceZone, VolumeDiscount,
+    },
 };
-use sg721::InstantiateMsg;
 
 const MINTER: &str = "minter";
 const PIXELS_PER_TILE: u32 = 100;
 const TILE_OWNER: &str = "tile_owner";
+const DEVELOPER: &str = "developer";
+const DEVELOPER_ROYALTIES: u8 = 5;
+const MINT_PRICE: u128 = 100_000_000;
+const MINT_DEVELOPER_FEE: u128 = MINT_PRICE * DEVELOPER_ROYALTIES as u128 / 100;
+
+fn config_msg() -> MosaicConfigMsg {
+    MosaicConfigMsg {
+        denom: "ustars".to_string(),
+        fee_tiers: FeeTiers {
+            tier_1h: Uint128::from(fees::FEE_TIER_1H),
+            tier_12h: Uint128::from(fees::FEE_TIER_12H),
+            tier_24h: Uint128::from(fees::FEE_TIER_24H),
+        },
+        developer_address: DEVELOPER.to_string(),
+        developer_royalties: DEVELOPER_ROYALTIES,
+        mint_price: Uint128::from(MINT_PRICE),
+        total_tiles: 10_000,
+        pixels_per_tile: PIXELS_PER_TILE,
+    }
+}
+
+fn config() -> MosaicConfig {
+    validate_config(&MockApi::default(), config_msg()).unwrap()
+}
+
+fn earnings(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, address: &str) -> Vec<Coin> {
+    let query_msg = QueryMsg::Earnings { address: address.to_string() };
+    let res: EarningsResponse = from_json(query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+    res.earnings
+}
 
+/// Answers the contract info query sg721 instantiation makes, tile owners come from the minted tokens
 fn mock_dependencies_with_querier() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
     let mut deps = mock_dependencies();
-    deps.querier.update_wasm(|query| {
-        match query {
-            cosmwasm_std::WasmQuery::Smart { contract_addr: _, msg } => {
-                // Parse the message
-                let query_msg: cw721::Cw721QueryMsg = from_json(msg).unwrap();
-                match query_msg {
-                    cw721::Cw721QueryMsg::OwnerOf { token_id: _, include_expired: _ } => {
-                        // Return mock owner response
-                        let response = cw721::OwnerOfResponse {
-                            owner: TILE_OWNER.to_string(),
-                            approvals: vec![],
-                        };
-                        SystemResult::Ok(ContractResult::Ok(to_json_binary(&response).unwrap()))
-                    }
-                    _ => SystemResult::Ok(ContractResult::Ok(Binary::from(br#"{"code_id":1,"creator":"creator","admin":null,"pinned":false,"ibc_port":null}"#.to_vec()))),
-                }
-            }
-            _ => SystemResult::Ok(ContractResult::Ok(Binary::from(br#"{"code_id":1,"creator":"creator","admin":null,"pinned":false,"ibc_port":null}"#.to_vec()))),
-        }
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::ContractInfo { .. } => SystemResult::Ok(ContractResult::Ok(Binary::from(
+            br#"{"code_id":1,"creator":"creator","admin":null,"pinned":false,"ibc_port":null}"#.to_vec(),
+        ))),
+        _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
     });
     deps
 }
 
+/// Mints `tile_id` to `TILE_OWNER` through MintTile, crediting `MINT_DEVELOPER_FEE` to the developer
+fn mint_to_owner(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, tile_id: u32) {
+    let msg = ExecuteMsg::MintTile { tile_id };
+    execute(deps.as_mut(), mock_env(), mock_info(TILE_OWNER, &coins(MINT_PRICE, "ustars")), msg).unwrap();
+}
+
 fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) {
     let env = mock_env();
     let info = mock_info(MINTER, &[]);
 
     // Initialize the contract
     let msg = InstantiateMsg {
-        name: "MosaicTiles".to_string(),
-        symbol: "TILE".to_string(),
-        minter: MINTER.to_string(),
-        collection_info: sg721::CollectionInfo {
-            creator: MINTER.to_string(),
-            description: "A mosaic NFT collection".to_string(),
-            image: "https://example.com/image.png".to_string(),
-            external_link: None,
-            royalty_info: None,
-            explicit_content: Some(false),
-            start_trading_time: None,
+        base: sg721::InstantiateMsg {
+            name: "MosaicTiles".to_string(),
+            symbol: "TILE".to_string(),
+            minter: MINTER.to_string(),
+            collection_info: sg721::CollectionInfo {
+                creator: MINTER.to_string(),
+                description: "A mosaic NFT collection".to_string(),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+                explicit_content: Some(false),
+                start_trading_time: None,
+            },
         },
+        config: config_msg(),
     };
 
     instantiate(deps.as_mut(), env, info, msg).unwrap();
@@ -72,30 +115,33 @@ fn set_pixel_color_invalid_pixel() {
 
     // Initialize the contract
     let msg = InstantiateMsg {
-        name: "MosaicTiles".to_string(),
-        symbol: "TILE".to_string(),
-        minter: MINTER.to_string(),
-        collection_info: sg721::CollectionInfo {
-            creator: MINTER.to_string(),
-            description: "A mosaic NFT collection".to_string(),
-            image: "https://example.com/image.png".to_string(),
-            external_link: None,
-            royalty_info: None,
-            explicit_content: Some(false),
-            start_trading_time: None,
+        base: sg721::InstantiateMsg {
+            name: "MosaicTiles".to_string(),
+            symbol: "TILE".to_string(),
+            minter: MINTER.to_string(),
+            collection_info: sg721::CollectionInfo {
+                creator: MINTER.to_string(),
+                description: "A mosaic NFT collection".to_string(),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+                explicit_content: Some(false),
+                start_trading_time: None,
+            },
         },
+        config: config_msg(),
     };
 
     let info = mock_info(MINTER, &[]); // No funds for instantiate
     instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     // Create a new tile
-    let tile = TileMetadata::new(0, env.block.time.seconds());
+    let tile = TileMetadata::new(0, PIXELS_PER_TILE);
     let current_tile_metadata = tile.to_bytes();
 
     // Calculate the required fee for 1 hour duration
     let duration = 3600u64; // 1 hour
-    let required_fee = fees::calculate_fee(duration);
+    let required_fee = fees::calculate_fee(&config(), duration).unwrap();
 
     // Try to update a pixel outside the tile range
     let msg = ExecuteMsg::SetPixelColor {
@@ -131,6 +177,25 @@ fn burn_is_disabled() {
     assert!(matches!(err, ContractError::FeatureDisabled { feature } if feature == "burn"));
 }
 
+#[test]
+fn base_mint_is_disabled() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+    let info = mock_info(MINTER, &[]);
+
+    // Tiles can only be minted with MintTile, even by the minter
+    let msg = ExecuteMsg::Base(sg721::ExecuteMsg::Mint {
+        token_id: "1".to_string(),
+        owner: MINTER.to_string(),
+        token_uri: None,
+        extension: Extension { tile_metadata: vec![] },
+    });
+
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert!(matches!(err, ContractError::FeatureDisabled { feature } if feature == "mint"));
+}
+
 #[test]
 fn set_pixel_color_fee_tiers() {
     let mut deps = mock_dependencies_with_querier();
@@ -138,26 +203,31 @@ fn set_pixel_color_fee_tiers() {
 
     // Initialize the contract
     let msg = InstantiateMsg {
-        name: "MosaicTiles".to_string(),
-        symbol: "TILE".to_string(),
-        minter: MINTER.to_string(),
-        collection_info: sg721::CollectionInfo {
-            creator: MINTER.to_string(),
-            description: "A mosaic NFT collection".to_string(),
-            image: "https://example.com/image.png".to_string(),
-            external_link: None,
-            royalty_info: None,
-            explicit_content: Some(false),
-            start_trading_time: None,
+        base: sg721::InstantiateMsg {
+            name: "MosaicTiles".to_string(),
+            symbol: "TILE".to_string(),
+            minter: MINTER.to_string(),
+            collection_info: sg721::CollectionInfo {
+                creator: MINTER.to_string(),
+                description: "A mosaic NFT collection".to_string(),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+                explicit_content: Some(false),
+                start_trading_time: None,
+            },
         },
+        config: config_msg(),
     };
 
     let info = mock_info(MINTER, &[]); // No funds for instantiate
     instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+    mint_to_owner(&mut deps, 0);
 
-    // Create a new tile
-    let tile = TileMetadata::new(0, env.block.time.seconds());
-    let current_tile_metadata = tile.to_bytes();
+    // Create a new tile, kept in sync with the stored hash after each update
+    let mut tile = TileMetadata::new(0, PIXELS_PER_TILE);
+    let mut developer_total = Uint128::from(MINT_DEVELOPER_FEE);
+    let mut owner_total = Uint128::zero();
 
     // Test different fee tiers
     let test_cases = vec![
@@ -168,14 +238,15 @@ fn set_pixel_color_fee_tiers() {
         (2 * fees::DAY, 4 * fees::FEE_TIER_24H), // 2 days -> 60 STARS (quadratic scaling)
     ];
 
-    for (duration, expected_fee) in test_cases {
+    for (pixel_id, (duration, expected_fee)) in (1..).zip(test_cases) {
+        let pixel_update = PixelUpdate {
+            pixel_id,
+            color: [255, 0, 0],
+            expiration: env.block.time.seconds() + duration,
+        };
         let msg = ExecuteMsg::SetPixelColor {
-            current_tile_metadata: current_tile_metadata.clone(),
-            pixel_update: PixelUpdate {
-                pixel_id: 1,
-                color: [255, 0, 0],
-                expiration: env.block.time.seconds() + duration,
-            },
+            current_tile_metadata: tile.to_bytes(),
+            pixel_update: pixel_update.clone(),
         };
 
         // Try with incorrect fee
@@ -193,37 +264,19 @@ fn set_pixel_color_fee_tiers() {
         // Try with correct fee
         let info = mock_info(MINTER, &coins(expected_fee, "ustars"));
         let response = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        tile.update_pixel(pixel_update.pixel_id, pixel_update.color, pixel_update.expiration, env.block.time.seconds()).unwrap();
 
         // Verify fee distribution
-        let developer_royalties = fees::developer_royalties() as u128;
+        let developer_royalties = DEVELOPER_ROYALTIES as u128;
         let developer_amount = Uint128::from(expected_fee).multiply_ratio(developer_royalties, 100u128);
         let owner_amount = Uint128::from(expected_fee) - developer_amount;
 
-        // Verify the bank messages
-        let messages = response.messages;
-        assert_eq!(messages.len(), 2);
-
-        // Verify developer fee
-        match &messages[0].msg {
-            cosmwasm_std::CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-                assert_eq!(to_address, &fees::developer_address());
-                assert_eq!(amount.len(), 1);
-                assert_eq!(amount[0].denom, "ustars");
-                assert_eq!(amount[0].amount, developer_amount);
-            }
-            _ => panic!("Expected bank message"),
-        }
-
-        // Verify owner fee
-        match &messages[1].msg {
-            cosmwasm_std::CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-                assert_eq!(to_address, TILE_OWNER);
-                assert_eq!(amount.len(), 1);
-                assert_eq!(amount[0].denom, "ustars");
-                assert_eq!(amount[0].amount, owner_amount);
-            }
-            _ => panic!("Expected bank message"),
-        }
+        // Fees are credited to the ledger instead of being sent right away
+        assert!(response.messages.is_empty());
+        developer_total += developer_amount;
+        owner_total += owner_amount;
+        assert_eq!(earnings(&deps, DEVELOPER), coins(developer_total.u128(), "ustars"));
+        assert_eq!(earnings(&deps, TILE_OWNER), coins(owner_total.u128(), "ustars"));
 
         // Verify attributes
         let attrs = response.attributes;
@@ -239,26 +292,29 @@ fn set_pixel_color_fee_per_second() {
 
     // Initialize the contract
     let msg = InstantiateMsg {
-        name: "MosaicTiles".to_string(),
-        symbol: "TILE".to_string(),
-        minter: MINTER.to_string(),
-        collection_info: sg721::CollectionInfo {
-            creator: MINTER.to_string(),
-            description: "A mosaic NFT collection".to_string(),
-            image: "https://example.com/image.png".to_string(),
-            external_link: None,
-            royalty_info: None,
-            explicit_content: Some(false),
-            start_trading_time: None,
+        base: sg721::InstantiateMsg {
+            name: "MosaicTiles".to_string(),
+            symbol: "TILE".to_string(),
+            minter: MINTER.to_string(),
+            collection_info: sg721::CollectionInfo {
+                creator: MINTER.to_string(),
+                description: "A mosaic NFT collection".to_string(),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+                explicit_content: Some(false),
+                start_trading_time: None,
+            },
         },
+        config: config_msg(),
     };
 
     let info = mock_info(MINTER, &[]); // No funds for instantiate
     instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+    mint_to_owner(&mut deps, 0);
 
-    // Create a new tile
-    let tile = TileMetadata::new(0, env.block.time.seconds());
-    let current_tile_metadata = tile.to_bytes();
+    // Create a new tile, kept in sync with the stored hash after each update
+    let mut tile = TileMetadata::new(0, PIXELS_PER_TILE);
 
     // Test per-second granularity for durations over 24h
     let base_duration = fees::DAY;
@@ -271,18 +327,19 @@ fn set_pixel_color_fee_per_second() {
 
     let mut last_duration: Option<u64> = None;
     let mut last_fee: Option<Coin> = None;
-    for duration in test_durations {
+    for (pixel_id, duration) in (1..).zip(test_durations) {
+        let pixel_update = PixelUpdate {
+            pixel_id,
+            color: [255, 0, 0],
+            expiration: env.block.time.seconds() + duration,
+        };
         let msg = ExecuteMsg::SetPixelColor {
-            current_tile_metadata: current_tile_metadata.clone(),
-            pixel_update: PixelUpdate {
-                pixel_id: 1,
-                color: [255, 0, 0],
-                expiration: env.block.time.seconds() + duration,
-            },
+            current_tile_metadata: tile.to_bytes(),
+            pixel_update: pixel_update.clone(),
         };
 
         // Calculate fee
-        let fee = fees::calculate_fee(duration);
+        let fee = fees::calculate_fee(&config(), duration).unwrap();
         
         // Verify fee increases with duration
         if let (Some(last_fee), Some(last_duration)) = (last_fee.as_ref(), last_duration) {
@@ -302,12 +359,886 @@ fn set_pixel_color_fee_per_second() {
         last_duration = Some(duration);
 
         // Try with the calculated fee
-        let info = mock_info(MINTER, &[fee.clone()]);
+        let info = mock_info(MINTER, std::slice::from_ref(&fee));
         let response = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        tile.update_pixel(pixel_update.pixel_id, pixel_update.color, pixel_update.expiration, env.block.time.seconds()).unwrap();
 
         // Verify attributes
         let attrs = response.attributes;
         assert!(attrs.iter().any(|attr| attr.key == "fee" && attr.value == fee.amount.to_string()));
         assert!(attrs.iter().any(|attr| attr.key == "duration" && attr.value == duration.to_string()));
     }
-} 
\ No newline at end of file
+} 
+#[test]
+fn set_pixel_colors_aggregates_fees() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_to_owner(&mut deps, 0);
+    mint_to_owner(&mut deps, 1);
+    let env = mock_env();
+
+    let tiles = vec![
+        TileMetadata::new(0, PIXELS_PER_TILE).to_bytes(),
+        TileMetadata::new(1, PIXELS_PER_TILE).to_bytes(),
+    ];
+    let expiration = env.block.time.seconds() + 30 * 60;
+    let updates = vec![
+        PixelUpdate { pixel_id: 1, color: [255, 0, 0], expiration },
+        PixelUpdate { pixel_id: 2, color: [0, 255, 0], expiration },
+        PixelUpdate { pixel_id: PIXELS_PER_TILE + 3, color: [0, 0, 255], expiration },
+    ];
+    let msg = ExecuteMsg::SetPixelColors {
+        current_tiles_metadata: tiles,
+        updates,
+    };
+
+    // A single payment covers every pixel
+    let expected_fee = 3 * fees::FEE_TIER_1H;
+    let info = mock_info(MINTER, &coins(fees::FEE_TIER_1H, "ustars"));
+    let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    match err {
+        ContractError::InvalidFee { expected, .. } => {
+            assert_eq!(expected.amount, Uint128::from(expected_fee));
+        }
+        _ => panic!("Expected InvalidFee error"),
+    }
+
+    let info = mock_info(MINTER, &coins(expected_fee, "ustars"));
+    let response = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // Both tiles belong to the same owner, so fees are credited once each
+    let developer_royalties = DEVELOPER_ROYALTIES as u128;
+    let developer_amount = Uint128::from(2 * fees::FEE_TIER_1H).multiply_ratio(developer_royalties, 100u128)
+        + Uint128::from(fees::FEE_TIER_1H).multiply_ratio(developer_royalties, 100u128);
+    let owner_amount = Uint128::from(expected_fee) - developer_amount;
+
+    assert!(response.messages.is_empty());
+    assert_eq!(earnings(&deps, DEVELOPER), coins(2 * MINT_DEVELOPER_FEE + developer_amount.u128(), "ustars"));
+    assert_eq!(earnings(&deps, TILE_OWNER), coins(owner_amount.u128(), "ustars"));
+
+    // Every pixel gets its own pixel_update event
+    assert_eq!(response.events.len(), 3);
+    let event = &response.events[2];
+    assert_eq!(event.ty, EVENT_TYPE_PIXEL_UPDATE);
+    let attr = |key: &str| event.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+    assert_eq!(attr("tile_pixel_id"), (PIXELS_PER_TILE + 3).to_string());
+    assert_eq!((attr("color_r"), attr("color_g"), attr("color_b")), ("0".to_string(), "0".to_string(), "255".to_string()));
+    assert_eq!(attr("version"), "1");
+    let metadata = PixelMetadata {
+        color: Color { r: 0, g: 0, b: 255 },
+        version: 1,
+        previous_hash: None,
+        timestamp: mock_env().block.time.seconds(),
+        expiration: Some(expiration),
+    };
+    assert_eq!(attr("metadata_hash"), metadata.hash().unwrap());
+
+    let attrs = response.attributes;
+    assert!(attrs.iter().any(|attr| attr.key == "pixel_count" && attr.value == "3"));
+    assert!(attrs.iter().any(|attr| attr.key == "tile_count" && attr.value == "2"));
+    assert!(attrs.iter().any(|attr| attr.key == "fee" && attr.value == expected_fee.to_string()));
+    assert!(attrs.iter().any(|attr| attr.key == "developer_fee" && attr.value == developer_amount.to_string()));
+}
+
+#[test]
+fn set_pixel_colors_rejects_locked_pixel() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_to_owner(&mut deps, 0);
+    let env = mock_env();
+
+    // Lock pixel 5
+    let mut tile = TileMetadata::new(0, PIXELS_PER_TILE);
+    let expiration = env.block.time.seconds() + 30 * 60;
+    let msg = ExecuteMsg::SetPixelColor {
+        current_tile_metadata: tile.to_bytes(),
+        pixel_update: PixelUpdate { pixel_id: 5, color: [1, 2, 3], expiration },
+    };
+    let info = mock_info(MINTER, &coins(fees::FEE_TIER_1H, "ustars"));
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    tile.update_pixel(5, [1, 2, 3], expiration, env.block.time.seconds()).unwrap();
+
+    let msg = ExecuteMsg::SetPixelColors {
+        current_tiles_metadata: vec![tile.to_bytes()],
+        updates: vec![
+            PixelUpdate { pixel_id: 4, color: [255, 0, 0], expiration },
+            PixelUpdate { pixel_id: 5, color: [255, 0, 0], expiration },
+        ],
+    };
+    let info = mock_info(MINTER, &coins(2 * fees::FEE_TIER_1H, "ustars"));
+    let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidPixelUpdate("Pixel is currently locked".to_string()));
+
+    // Every touched tile must be provided
+    let msg = ExecuteMsg::SetPixelColors {
+        current_tiles_metadata: vec![tile.to_bytes()],
+        updates: vec![PixelUpdate { pixel_id: PIXELS_PER_TILE, color: [255, 0, 0], expiration }],
+    };
+    let info = mock_info(MINTER, &coins(fees::FEE_TIER_1H, "ustars"));
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPixelUpdate(_)));
+}
+
+#[test]
+fn set_pixel_color_verifies_tile_hash() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+
+    // Tiles start out committed to their default metadata
+    let mut tile = TileMetadata::new(0, PIXELS_PER_TILE);
+    let res: TileHashResponse =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::TileHash { tile_id: 0 }).unwrap()).unwrap();
+    assert_eq!(res.hash.as_slice(), tile.calculate_hash().as_slice());
+
+    // Metadata that does not match the commitment is rejected
+    let mut forged = tile.clone();
+    forged.pixels[3].color = [0, 0, 0];
+    let pixel_update = PixelUpdate {
+        pixel_id: 1,
+        color: [255, 0, 0],
+        expiration: env.block.time.seconds() + 60,
+    };
+    let msg = ExecuteMsg::SetPixelColor {
+        current_tile_metadata: forged.to_bytes(),
+        pixel_update: pixel_update.clone(),
+    };
+    let info = mock_info(MINTER, &coins(fees::FEE_TIER_1H, "ustars"));
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::TileHashMismatch { tile_id: 0 });
+
+    // Rentals are paid to the tile holder, so the tile must be minted first
+    let msg = ExecuteMsg::SetPixelColor {
+        current_tile_metadata: tile.to_bytes(),
+        pixel_update: pixel_update.clone(),
+    };
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::TileNotMinted { tile_id: 0 });
+    mint_to_owner(&mut deps, 0);
+
+    // The hash of the updated metadata is stored and returned
+    let response = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    tile.update_pixel(pixel_update.pixel_id, pixel_update.color, pixel_update.expiration, env.block.time.seconds()).unwrap();
+    let new_hash = cosmwasm_std::HexBinary::from(tile.calculate_hash());
+    assert!(response.attributes.iter().any(|attr| attr.key == "tile_hash" && attr.value == new_hash.to_hex()));
+
+    let res: TileHashResponse =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::TileHash { tile_id: 0 }).unwrap()).unwrap();
+    assert_eq!(res.hash, new_hash);
+
+    // The NFT extension follows the metadata
+    let res: cw721::NftInfoResponse<crate::types::Extension> = from_json(
+        query(deps.as_ref(), env.clone(), QueryMsg::Base(sg721_base::msg::QueryMsg::NftInfo { token_id: "0".to_string() })).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.extension.tile_metadata, tile.to_bytes());
+
+    // Replaying the stale metadata no longer works
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(err, ContractError::TileHashMismatch { tile_id: 0 });
+}
+
+#[test]
+fn calculate_fee_tier_boundaries() {
+    let amount = |duration: u64| fees::calculate_fee(&config(), duration).unwrap().amount.u128();
+
+    assert_eq!(amount(0), fees::FEE_TIER_1H);
+    assert_eq!(amount(fees::HOUR - 1), fees::FEE_TIER_1H);
+    assert_eq!(amount(fees::HOUR), fees::FEE_TIER_12H);
+    assert_eq!(amount(12 * fees::HOUR - 1), fees::FEE_TIER_12H);
+    assert_eq!(amount(12 * fees::HOUR), fees::FEE_TIER_24H);
+    assert_eq!(amount(fees::DAY - 1), fees::FEE_TIER_24H);
+    assert_eq!(amount(fees::DAY), fees::FEE_TIER_24H);
+
+    // Quadratic scaling past 24h is exact on whole ratios
+    assert_eq!(amount(2 * fees::DAY), 4 * fees::FEE_TIER_24H);
+    assert_eq!(amount(3 * fees::DAY), 9 * fees::FEE_TIER_24H);
+    assert_eq!(amount(fees::DAY + fees::DAY / 2), 9 * fees::FEE_TIER_24H / 4);
+
+    // Fractional results are rounded down
+    assert_eq!(amount(fees::DAY + 1), 15_000_347);
+}
+
+#[test]
+fn calculate_fee_grows_monotonically_past_24h() {
+    let mut last = fees::calculate_fee(&config(), fees::DAY).unwrap().amount;
+    for duration in (fees::DAY + 1..=30 * fees::DAY).step_by(997) {
+        let fee = fees::calculate_fee(&config(), duration).unwrap().amount;
+        assert!(fee > last, "Fee should increase with duration at {duration}s");
+        last = fee;
+    }
+
+    // The largest duration still fits without overflowing
+    let fee = fees::calculate_fee(&config(), u64::MAX).unwrap().amount;
+    assert!(fee > last);
+}
+
+#[test]
+fn instantiate_validates_config() {
+    let mut deps = mock_dependencies_with_querier();
+    let env = mock_env();
+
+    let mut msg = InstantiateMsg {
+        base: sg721::InstantiateMsg {
+            name: "MosaicTiles".to_string(),
+            symbol: "TILE".to_string(),
+            minter: MINTER.to_string(),
+            collection_info: sg721::CollectionInfo {
+                creator: MINTER.to_string(),
+                description: "A mosaic NFT collection".to_string(),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+                explicit_content: Some(false),
+                start_trading_time: None,
+            },
+        },
+        config: config_msg(),
+    };
+    msg.config.developer_royalties = 101;
+
+    let err = instantiate(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidConfig(_)));
+
+    msg.config = config_msg();
+    msg.config.developer_address = String::new();
+    instantiate(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg.clone()).unwrap_err();
+
+    // Fee tiers must be non-zero and strictly increasing
+    let invalid_tiers: [(u128, u128, u128); 3] = [(0, 10, 15), (10, 10, 15), (5, 15, 10)];
+    for (tier_1h, tier_12h, tier_24h) in invalid_tiers {
+        msg.config = config_msg();
+        msg.config.fee_tiers = FeeTiers {
+            tier_1h: Uint128::from(tier_1h),
+            tier_12h: Uint128::from(tier_12h),
+            tier_24h: Uint128::from(tier_24h),
+        };
+        let err = instantiate(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::InvalidConfig("Fee tiers must be non-zero and strictly increasing".to_string()));
+    }
+
+    msg.config = config_msg();
+    instantiate(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap();
+    let res: MosaicConfig = from_json(query(deps.as_ref(), env, QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(res, config());
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+
+    let update = UpdateConfigMsg {
+        fee_tiers: Some(FeeTiers {
+            tier_1h: Uint128::from(1u128),
+            tier_12h: Uint128::from(2u128),
+            tier_24h: Uint128::from(3u128),
+        }),
+        developer_address: Some("new_developer".to_string()),
+        ..Default::default()
+    };
+    let msg = ExecuteMsg::UpdateConfig { config: update.clone() };
+
+    // Only the owner can update the config
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Updates are validated
+    let invalid = ExecuteMsg::UpdateConfig {
+        config: UpdateConfigMsg {
+            developer_royalties: Some(150),
+            ..Default::default()
+        },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), invalid).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidConfig(_)));
+    let invalid = ExecuteMsg::UpdateConfig {
+        config: UpdateConfigMsg {
+            fee_tiers: Some(FeeTiers {
+                tier_1h: Uint128::zero(),
+                tier_12h: Uint128::from(2u128),
+                tier_24h: Uint128::from(3u128),
+            }),
+            ..Default::default()
+        },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), invalid).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidConfig(_)));
+
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap();
+    let res: MosaicConfig = from_json(query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(res.fee_tiers, update.fee_tiers.unwrap());
+    assert_eq!(res.developer_address, "new_developer");
+    assert_eq!(res.developer_royalties, DEVELOPER_ROYALTIES);
+    assert_eq!(res.pixels_per_tile, PIXELS_PER_TILE);
+
+    // New fees apply to the next update
+    mint_to_owner(&mut deps, 0);
+    let msg = ExecuteMsg::SetPixelColor {
+        current_tile_metadata: TileMetadata::new(0, PIXELS_PER_TILE).to_bytes(),
+        pixel_update: PixelUpdate {
+            pixel_id: 1,
+            color: [255, 0, 0],
+            expiration: env.block.time.seconds() + 60,
+        },
+    };
+    execute(deps.as_mut(), env, mock_info(MINTER, &coins(1, "ustars")), msg).unwrap();
+}
+
+#[test]
+fn mint_tile() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+    let buyer = "buyer";
+
+    // Tile IDs beyond the mosaic are rejected
+    let msg = ExecuteMsg::MintTile { tile_id: config().total_tiles };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(buyer, &coins(MINT_PRICE, "ustars")), msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidTileId {});
+
+    // The exact mint price is required
+    let msg = ExecuteMsg::MintTile { tile_id: 7 };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(buyer, &coins(MINT_PRICE - 1, "ustars")), msg.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFee { .. }));
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info(buyer, &coins(MINT_PRICE, "ustars")), msg.clone()).unwrap();
+    let attr = |key: &str| res.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+    assert_eq!(
+        (attr("action"), attr("minter"), attr("owner"), attr("token_id")),
+        ("mint".to_string(), buyer.to_string(), buyer.to_string(), "7".to_string())
+    );
+    let developer_amount = Uint128::from(MINT_PRICE).multiply_ratio(DEVELOPER_ROYALTIES as u128, 100u128);
+    assert_eq!(earnings(&deps, DEVELOPER), coins(developer_amount.u128(), "ustars"));
+    assert_eq!(earnings(&deps, MINTER), coins(MINT_PRICE - developer_amount.u128(), "ustars"));
+
+    // The token is owned by the buyer and carries default tile metadata
+    let res: cw721::NftInfoResponse<crate::types::Extension> = from_json(
+        query(deps.as_ref(), env.clone(), QueryMsg::Base(sg721_base::msg::QueryMsg::NftInfo { token_id: "7".to_string() })).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.extension.tile_metadata, TileMetadata::new(7, PIXELS_PER_TILE).to_bytes());
+    let res: cw721::OwnerOfResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Base(sg721_base::msg::QueryMsg::OwnerOf { token_id: "7".to_string(), include_expired: None }),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.owner, buyer);
+
+    // A position can only be minted once
+    let err = execute(deps.as_mut(), env, mock_info("other", &coins(MINT_PRICE, "ustars")), msg).unwrap_err();
+    assert_eq!(err, ContractError::TileAlreadyMinted { tile_id: 7 });
+}
+
+#[test]
+fn withdraw_earnings() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+
+    let msg = ExecuteMsg::MintTile { tile_id: 1 };
+    execute(deps.as_mut(), env.clone(), mock_info("buyer", &coins(MINT_PRICE, "ustars")), msg).unwrap();
+    let developer_amount = Uint128::from(MINT_PRICE).multiply_ratio(DEVELOPER_ROYALTIES as u128, 100u128);
+
+    // Nothing to withdraw without earnings
+    let msg = ExecuteMsg::Withdraw { amount: None };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("buyer", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::NoEarnings {});
+
+    // Withdrawals cannot exceed the balance
+    let requested = Coin { denom: "ustars".to_string(), amount: developer_amount + Uint128::one() };
+    let msg = ExecuteMsg::Withdraw { amount: Some(requested.clone()) };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(DEVELOPER, &[]), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientEarnings {
+            requested,
+            available: Coin { denom: "ustars".to_string(), amount: developer_amount },
+        }
+    );
+
+    // Partial withdrawal
+    let msg = ExecuteMsg::Withdraw { amount: Some(Coin { denom: "ustars".to_string(), amount: Uint128::from(1_000u128) }) };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(DEVELOPER, &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        BankMsg::Send { to_address: DEVELOPER.to_string(), amount: coins(1_000, "ustars") }.into()
+    );
+    assert_eq!(earnings(&deps, DEVELOPER), coins(developer_amount.u128() - 1_000, "ustars"));
+
+    // Withdraw everything that is left
+    let msg = ExecuteMsg::Withdraw { amount: None };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(DEVELOPER, &[]), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        BankMsg::Send { to_address: DEVELOPER.to_string(), amount: coins(developer_amount.u128() - 1_000, "ustars") }.into()
+    );
+    assert!(earnings(&deps, DEVELOPER).is_empty());
+    let err = execute(deps.as_mut(), env, mock_info(DEVELOPER, &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::NoEarnings {});
+}
+
+#[test]
+fn set_pixel_color_chains_versions() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_to_owner(&mut deps, 0);
+    let mut env = mock_env();
+    let mut tile = TileMetadata::new(0, PIXELS_PER_TILE);
+
+    let mut hashes = vec![];
+    for (version, color) in [(1u32, [1, 1, 1]), (2, [2, 2, 2])] {
+        let pixel_update = PixelUpdate {
+            pixel_id: 7,
+            color,
+            expiration: env.block.time.seconds() + 60,
+        };
+        let msg = ExecuteMsg::SetPixelColor {
+            current_tile_metadata: tile.to_bytes(),
+            pixel_update: pixel_update.clone(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &coins(fees::FEE_TIER_1H, "ustars")), msg).unwrap();
+        tile.update_pixel(7, color, pixel_update.expiration, env.block.time.seconds()).unwrap();
+
+        // The stored pixel carries the version and the hash of the previous version
+        let pixel = tile.get_pixel(7).unwrap();
+        assert_eq!(pixel.version, version);
+        assert_eq!(pixel.previous_hash, hashes.last().cloned());
+
+        let attr = |key: &str| res.events[0].attributes.iter().find(|attr| attr.key == key).map(|attr| attr.value.clone());
+        assert_eq!(attr("version").unwrap(), version.to_string());
+        assert_eq!(attr("previous_hash"), hashes.last().cloned());
+        hashes.push(attr("metadata_hash").unwrap());
+        assert_eq!(pixel.metadata().unwrap().hash().unwrap(), *hashes.last().unwrap());
+
+        env.block.time = env.block.time.plus_seconds(60);
+    }
+}
+
+#[test]
+fn pause_status() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+    let buyer = "buyer";
+    let pause = |status: PauseStatus| ExecuteMsg::UpdatePauseStatus { status };
+    let mint = ExecuteMsg::MintTile { tile_id: 7 };
+    let transfer = ExecuteMsg::Base(sg721::ExecuteMsg::TransferNft {
+        recipient: "recipient".to_string(),
+        token_id: "7".to_string(),
+    });
+
+    // Only the owner can pause
+    let all = PauseStatus { painting: true, minting: true, transfers: true };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), pause(all.clone())).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), pause(all.clone())).unwrap();
+    let status: PauseStatus = from_json(query(deps.as_ref(), env.clone(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+    assert_eq!(status, all);
+
+    // Paused actions are rejected before anything else is checked
+    let paint = ExecuteMsg::SetPixelColor {
+        current_tile_metadata: vec![],
+        pixel_update: PixelUpdate { pixel_id: 0, color: [1, 2, 3], expiration: env.block.time.seconds() + 60 },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint).unwrap_err();
+    assert_eq!(err, ContractError::Paused { action: "painting".to_string() });
+    let err = execute(deps.as_mut(), env.clone(), mock_info(buyer, &coins(MINT_PRICE, "ustars")), mint.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Paused { action: "minting".to_string() });
+
+    // Flags are independent
+    let transfers = PauseStatus { transfers: true, ..PauseStatus::default() };
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), pause(transfers)).unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info(buyer, &coins(MINT_PRICE, "ustars")), mint).unwrap();
+    let err = execute(deps.as_mut(), env.clone(), mock_info(buyer, &[]), transfer.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Paused { action: "transfers".to_string() });
+
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), pause(PauseStatus::default())).unwrap();
+    execute(deps.as_mut(), env, mock_info(buyer, &[]), transfer).unwrap();
+}
+
+#[test]
+fn position_pricing() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+    let expiration = env.block.time.seconds() + 60;
+    let update = |tile_id: u32| PixelUpdate { pixel_id: tile_id * PIXELS_PER_TILE, color: [1, 2, 3], expiration };
+    let estimate = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, tile_ids: &[u32]| -> FeeEstimateResponse {
+        let msg = QueryMsg::EstimateFee { updates: tile_ids.iter().copied().map(update).collect() };
+        from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap()
+    };
+
+    // Without position pricing every tile costs the duration fee
+    let res = estimate(&deps, &[0, 101]);
+    assert_eq!(res.fee, coins(2 * fees::FEE_TIER_1H, "ustars")[0]);
+
+    // 100x100 tiles with premium edges, corners and a prime center
+    let pricing = PositionPricing {
+        tiles_per_row: 100,
+        edge_weight: 15_000,
+        corner_weight: 20_000,
+        zones: vec![PriceZone { x: 45, y: 45, width: 10, height: 10, weight: 30_000 }],
+    };
+    let msg = ExecuteMsg::UpdatePositionPricing { pricing: Some(pricing.clone()) };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let invalid = PositionPricing { tiles_per_row: 0, ..pricing.clone() };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), ExecuteMsg::UpdatePositionPricing { pricing: Some(invalid) }).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidConfig(_)));
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap();
+    let res: Option<PositionPricing> = from_json(query(deps.as_ref(), env.clone(), QueryMsg::PositionPricing {}).unwrap()).unwrap();
+    assert_eq!(res, Some(pricing));
+
+    // Corner, edge, bottom corner, inner and prime tiles
+    let res = estimate(&deps, &[0, 1, 9_999, 101, 5_050]);
+    let base = fees::FEE_TIER_1H;
+    let expected = [2 * base, 3 * base / 2, 2 * base, base, 3 * base];
+    assert_eq!(res.pixel_fees, expected.map(Uint128::from).to_vec());
+    assert_eq!(res.fee, coins(expected.iter().sum(), "ustars")[0]);
+
+    // Painting charges the estimated fee
+    mint_to_owner(&mut deps, 5_050);
+    let tile = TileMetadata::new(5_050, PIXELS_PER_TILE);
+    let msg = ExecuteMsg::SetPixelColor { current_tile_metadata: tile.to_bytes(), pixel_update: update(5_050) };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &coins(base, "ustars")), msg.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFee { .. }));
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &coins(3 * base, "ustars")), msg).unwrap();
+
+    // Removing the pricing restores flat fees
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), ExecuteMsg::UpdatePositionPricing { pricing: None }).unwrap();
+    assert_eq!(estimate(&deps, &[0]).fee, coins(base, "ustars")[0]);
+}
+
+#[test]
+fn volume_discounts() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+    let base = fees::FEE_TIER_1H;
+    let expiration = env.block.time.seconds() + 60;
+    let updates = |count: u32| (0..count).map(|pixel_id| PixelUpdate { pixel_id, color: [1, 2, 3], expiration }).collect::<Vec<_>>();
+    let update = |discounts: Vec<VolumeDiscount>| ExecuteMsg::UpdateVolumeDiscounts { discounts };
+
+    // Tiers must grow in size and discount
+    let discounts = vec![
+        VolumeDiscount { min_pixels: 2, discount: 1_000 },
+        VolumeDiscount { min_pixels: 3, discount: 2_500 },
+    ];
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), update(discounts.clone())).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let unordered = discounts.iter().rev().cloned().collect();
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(unordered)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidConfig(_)));
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(discounts.clone())).unwrap();
+    let res: Vec<VolumeDiscount> = from_json(query(deps.as_ref(), env.clone(), QueryMsg::VolumeDiscounts {}).unwrap()).unwrap();
+    assert_eq!(res, discounts);
+
+    // The largest tier reached discounts every pixel of the batch
+    let estimate = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, count: u32| -> FeeEstimateResponse {
+        let msg = QueryMsg::EstimateFee { updates: updates(count) };
+        from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap()
+    };
+    assert_eq!(estimate(&deps, 1).fee.amount.u128(), base);
+    let res = estimate(&deps, 2);
+    assert_eq!((res.discount, res.fee.amount.u128()), (1_000, 2 * base * 9 / 10));
+    let res = estimate(&deps, 3);
+    assert_eq!((res.discount, res.fee.amount.u128()), (2_500, 3 * base * 3 / 4));
+
+    mint_to_owner(&mut deps, 0);
+    let msg = ExecuteMsg::SetPixelColors {
+        current_tiles_metadata: vec![TileMetadata::new(0, PIXELS_PER_TILE).to_bytes()],
+        updates: updates(3),
+    };
+    let response = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &coins(3 * base * 3 / 4, "ustars")), msg).unwrap();
+    let attr = |key: &str| response.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+    assert_eq!(attr("volume_discount"), "2500");
+    assert_eq!(attr("pixel_fee_2"), (base * 3 / 4).to_string());
+    assert_eq!(attr("fee"), (3 * base * 3 / 4).to_string());
+
+    // An empty schedule removes the discounts
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(vec![])).unwrap();
+    assert_eq!(estimate(&deps, 3).fee.amount.u128(), 3 * base);
+}
+
+#[test]
+fn rental_split_follows_royalty_info() {
+    let mut deps = mock_dependencies_with_querier();
+    let mut env = mock_env();
+    let artist = "artist";
+    let msg = InstantiateMsg {
+        base: sg721::InstantiateMsg {
+            name: "MosaicTiles".to_string(),
+            symbol: "TILE".to_string(),
+            minter: MINTER.to_string(),
+            collection_info: sg721::CollectionInfo {
+                creator: MINTER.to_string(),
+                description: "A mosaic NFT collection".to_string(),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: Some(sg721::RoyaltyInfoResponse {
+                    payment_address: artist.to_string(),
+                    share: Decimal::percent(10),
+                }),
+                explicit_content: Some(false),
+                start_trading_time: None,
+            },
+        },
+        config: config_msg(),
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap();
+    mint_to_owner(&mut deps, 0);
+
+    let mut tile = TileMetadata::new(0, PIXELS_PER_TILE);
+    let mut paint = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, env: &Env, pixel_id: u32| {
+        let pixel_update = PixelUpdate { pixel_id, color: [1, 2, 3], expiration: env.block.time.seconds() + 60 };
+        let msg = ExecuteMsg::SetPixelColor { current_tile_metadata: tile.to_bytes(), pixel_update: pixel_update.clone() };
+        execute(deps.as_mut(), env.clone(), mock_info(MINTER, &coins(fees::FEE_TIER_1H, "ustars")), msg).unwrap();
+        tile.update_pixel(pixel_id, pixel_update.color, pixel_update.expiration, env.block.time.seconds()).unwrap();
+    };
+
+    // The royalty recipient takes the royalty share instead of the developer
+    paint(&mut deps, &env, 0);
+    let royalty = fees::FEE_TIER_1H / 10;
+    assert_eq!(earnings(&deps, artist), coins(royalty, "ustars"));
+    assert_eq!(earnings(&deps, TILE_OWNER), coins(fees::FEE_TIER_1H - royalty, "ustars"));
+    assert_eq!(earnings(&deps, DEVELOPER), coins(MINT_DEVELOPER_FEE, "ustars"));
+
+    // Royalty updates through the collection info govern later rentals
+    env.block.time = env.block.time.plus_seconds(fees::DAY);
+    let update = ExecuteMsg::Base(sg721::ExecuteMsg::UpdateCollectionInfo {
+        collection_info: sg721::UpdateCollectionInfoMsg {
+            description: None,
+            image: None,
+            external_link: None,
+            explicit_content: Some(false),
+            royalty_info: Some(Some(sg721::RoyaltyInfoResponse {
+                payment_address: artist.to_string(),
+                share: Decimal::percent(4),
+            })),
+            creator: None,
+        },
+    });
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update).unwrap();
+    paint(&mut deps, &env, 1);
+    let new_royalty = fees::FEE_TIER_1H / 25;
+    assert_eq!(earnings(&deps, artist), coins(royalty + new_royalty, "ustars"));
+    assert_eq!(earnings(&deps, TILE_OWNER), coins(2 * fees::FEE_TIER_1H - royalty - new_royalty, "ustars"));
+}
+
+#[test]
+fn fee_split() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+    let treasury = Addr::unchecked("treasury");
+    let update = |shares: Vec<FeeShare>| ExecuteMsg::UpdateFeeSplit { shares };
+    let share = |recipient: FeeRecipient, weight: u32| FeeShare { recipient, weight };
+
+    // Weights must sum to 100% across distinct recipients
+    let shares = vec![share(FeeRecipient::TileOwner, 7_000), share(FeeRecipient::Address(treasury.clone()), 3_000)];
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), update(shares.clone())).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let short = vec![share(FeeRecipient::TileOwner, 7_000)];
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(short)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidConfig(_)));
+    let duplicate = vec![share(FeeRecipient::TileOwner, 5_000), share(FeeRecipient::TileOwner, 5_000)];
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(duplicate)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidConfig(_)));
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(shares.clone())).unwrap();
+    let res: Vec<FeeShare> = from_json(query(deps.as_ref(), env.clone(), QueryMsg::FeeSplit {}).unwrap()).unwrap();
+    assert_eq!(res, shares);
+
+    // What is left after royalties is split between the recipients
+    mint_to_owner(&mut deps, 0);
+    let msg = ExecuteMsg::SetPixelColor {
+        current_tile_metadata: TileMetadata::new(0, PIXELS_PER_TILE).to_bytes(),
+        pixel_update: PixelUpdate { pixel_id: 0, color: [1, 2, 3], expiration: env.block.time.seconds() + 60 },
+    };
+    let response = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &coins(fees::FEE_TIER_1H, "ustars")), msg).unwrap();
+    let royalty = fees::FEE_TIER_1H * DEVELOPER_ROYALTIES as u128 / 100;
+    let treasury_amount = (fees::FEE_TIER_1H - royalty) * 3 / 10;
+    assert_eq!(earnings(&deps, DEVELOPER), coins(MINT_DEVELOPER_FEE + royalty, "ustars"));
+    assert_eq!(earnings(&deps, "treasury"), coins(treasury_amount, "ustars"));
+    assert_eq!(earnings(&deps, TILE_OWNER), coins(fees::FEE_TIER_1H - royalty - treasury_amount, "ustars"));
+    let attr = |key: &str| response.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+    assert_eq!(attr("fee_treasury"), treasury_amount.to_string());
+
+    // Rounding leftovers go to the first recipient
+    let owner = Addr::unchecked(TILE_OWNER);
+    let thirds = vec![
+        share(FeeRecipient::Address(treasury.clone()), 3_333),
+        share(FeeRecipient::TileOwner, 3_333),
+        share(FeeRecipient::Address(Addr::unchecked("community")), 3_334),
+    ];
+    let amounts = fees::split_shares(Uint128::new(10), &thirds, &owner);
+    assert_eq!(
+        amounts,
+        vec![(treasury, Uint128::new(4)), (owner.clone(), Uint128::new(3)), (Addr::unchecked("community"), Uint128::new(3))]
+    );
+    assert_eq!(fees::split_shares(Uint128::new(10), &[], &owner), vec![(owner, Uint128::new(10))]);
+}
+
+// Tile encoding of 0.1.0, written by a stand-in for the old code
+#[derive(Serialize, Deserialize)]
+struct LegacyPixel {
+    color: [u8; 3],
+    expiration: u64,
+    pixel_id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LegacyTile {
+    tile_id: u32,
+    pixels: Vec<LegacyPixel>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, schemars::JsonSchema)]
+struct LegacyInstantiateMsg {
+    contract: String,
+    version: String,
+}
+
+fn legacy_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: LegacyInstantiateMsg,
+) -> StdResult<Response<StargazeMsgWrapper>> {
+    cw2::set_contract_version(deps.storage, msg.contract, msg.version)?;
+    let tile = LegacyTile {
+        tile_id: 1,
+        pixels: (0..PIXELS_PER_TILE)
+            .map(|index| LegacyPixel { color: [255, 0, 0], expiration: 42, pixel_id: PIXELS_PER_TILE + index })
+            .collect(),
+    };
+    let token = TokenInfo {
+        owner: Addr::unchecked(TILE_OWNER),
+        approvals: vec![],
+        token_uri: None,
+        extension: Extension { tile_metadata: bincode::serialize(&tile).unwrap() },
+    };
+    let base = sg721_base::Sg721Contract::<Extension>::default();
+    base.parent.tokens.save(deps.storage, "1", &token)?;
+    Ok(Response::new())
+}
+
+fn legacy_execute(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response<StargazeMsgWrapper>> {
+    Ok(Response::new())
+}
+
+fn legacy_query(_deps: cosmwasm_std::Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    Ok(Binary::default())
+}
+
+/// Instantiates the legacy contract with the given cw2 info and stores the current code
+fn setup_legacy_contract(
+    app: &mut BasicApp<StargazeMsgWrapper>,
+    contract: &str,
+    version: &str,
+) -> (Addr, u64) {
+    let legacy_code_id = app.store_code(Box::new(ContractWrapper::new(
+        legacy_execute,
+        legacy_instantiate,
+        legacy_query,
+    )));
+    let code_id = app.store_code(Box::new(
+        ContractWrapper::new(execute, instantiate, query).with_migrate(migrate),
+    ));
+    let addr = app
+        .instantiate_contract(
+            legacy_code_id,
+            Addr::unchecked(MINTER),
+            &LegacyInstantiateMsg { contract: contract.to_string(), version: version.to_string() },
+            &[],
+            "mosaic",
+            Some(MINTER.to_string()),
+        )
+        .unwrap();
+    (addr, code_id)
+}
+
+#[test]
+fn migrate_from_v0_1_0() {
+    let mut app = custom_app::<StargazeMsgWrapper, Empty, _>(|_, _, _| {});
+    let (addr, code_id) = setup_legacy_contract(&mut app, "crates.io:mosaic-nft", "0.1.0");
+
+    // 0.1.0 had no config to migrate
+    let err = app
+        .migrate_contract(Addr::unchecked(MINTER), addr.clone(), &MigrateMsg::default(), code_id)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MigrationError("config is required to migrate from 0.1.0".to_string())
+    );
+
+    let msg = MigrateMsg { config: Some(config_msg()) };
+    let res = app.migrate_contract(Addr::unchecked(MINTER), addr.clone(), &msg, code_id).unwrap();
+    let wasm = res.events.iter().find(|event| event.ty == "wasm").unwrap();
+    assert!(wasm.attributes.iter().any(|attr| attr.key == "migration" && attr.value == "0.2.0"));
+
+    let version = cw2::query_contract_info(&app.wrap(), addr.to_string()).unwrap();
+    assert_eq!(version.contract, "crates.io:mosaic-tiles");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+    let stored: MosaicConfig = app.wrap().query_wasm_smart(&addr, &QueryMsg::Config {}).unwrap();
+    assert_eq!(stored, config());
+
+    // Tile metadata is re-encoded with versioned pixels and its hash recorded
+    let nft: NftInfoResponse<Extension> = app
+        .wrap()
+        .query_wasm_smart(&addr, &QueryMsg::Base(BaseQueryMsg::NftInfo { token_id: "1".to_string() }))
+        .unwrap();
+    let tile_metadata = TileMetadata::from_bytes(&nft.extension.tile_metadata).unwrap();
+    assert_eq!(tile_metadata.tile_id, 1);
+    assert_eq!(tile_metadata.pixels.len(), PIXELS_PER_TILE as usize);
+    assert_eq!(tile_metadata.pixels[0].color, [255, 0, 0]);
+    assert_eq!(tile_metadata.pixels[0].expiration, 42);
+    assert_eq!(tile_metadata.pixels[0].version, 0);
+
+    let hash: TileHashResponse = app
+        .wrap()
+        .query_wasm_smart(&addr, &QueryMsg::TileHash { tile_id: 1 })
+        .unwrap();
+    assert_eq!(hash.hash, tile_metadata.calculate_hash().as_slice());
+}
+
+#[test]
+fn migrate_rejects_downgrade_and_other_contracts() {
+    let mut app = custom_app::<StargazeMsgWrapper, Empty, _>(|_, _, _| {});
+    let msg = MigrateMsg { config: Some(config_msg()) };
+
+    let (addr, code_id) = setup_legacy_contract(&mut app, "crates.io:mosaic-tiles", "99.0.0");
+    let err = app.migrate_contract(Addr::unchecked(MINTER), addr, &msg, code_id).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MigrationError(format!(
+            "cannot downgrade from 99.0.0 to {}",
+            env!("CARGO_PKG_VERSION")
+        ))
+    );
+
+    // The canvas contract and the shared 0.1.0 name past 0.1.0 are other contracts
+    for (contract, version) in [
+        ("crates.io:other", "0.1.0"),
+        ("crates.io:mosaic-canvas", env!("CARGO_PKG_VERSION")),
+        ("crates.io:mosaic-nft", "0.2.0"),
+    ] {
+        let (addr, code_id) = setup_legacy_contract(&mut app, contract, version);
+        let err = app.migrate_contract(Addr::unchecked(MINTER), addr, &msg, code_id).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::MigrationError(format!("cannot migrate from contract {contract}"))
+        );
+    }
+}
@@ -1,6 +1,11 @@
 use cosmwasm_std::{Env, MessageInfo, OwnedDeps};
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
-use sg721::InstantiateMsg;
+use cosmwasm_std::Uint128;
+use mosaic_contract::{
+    constants::fees,
+    msg::{InstantiateMsg, MosaicConfigMsg},
+    state::FeeTiers,
+};
 
 /// Helper function to setup a contract for testing
 pub fn setup_contract() -> (OwnedDeps<MockStorage, MockApi, MockQuerier>, Env, MessageInfo) {
@@ -8,19 +13,34 @@ pub fn setup_contract() -> (OwnedDeps<MockStorage, MockApi, MockQuerier>, Env, M
     let env = mock_env();
     let info = mock_info("creator", &[]);
 
-    // Create a basic sg721 instantiate message
+    // Create a basic sg721 instantiate message with the default fee tiers
     let msg = InstantiateMsg {
-        name: "Mosaic NFT".to_string(),
-        symbol: "MOSAIC".to_string(),
-        minter: "creator".to_string(),
-        collection_info: sg721::CollectionInfo {
-            creator: "creator".to_string(),
-            description: "A mosaic NFT collection".to_string(),
-            image: "https://example.com/image.png".to_string(),
-            external_link: None,
-            royalty_info: None,
-            explicit_content: Some(false),
-            start_trading_time: None,
+        base: sg721::InstantiateMsg {
+            name: "Mosaic NFT".to_string(),
+            symbol: "MOSAIC".to_string(),
+            minter: "creator".to_string(),
+            collection_info: sg721::CollectionInfo {
+                creator: "creator".to_string(),
+                description: "A mosaic NFT collection".to_string(),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+                explicit_content: Some(false),
+                start_trading_time: None,
+            },
+        },
+        config: MosaicConfigMsg {
+            denom: "ustars".to_string(),
+            fee_tiers: FeeTiers {
+                tier_1h: Uint128::from(fees::FEE_TIER_1H),
+                tier_12h: Uint128::from(fees::FEE_TIER_12H),
+                tier_24h: Uint128::from(fees::FEE_TIER_24H),
+            },
+            developer_address: "developer".to_string(),
+            developer_royalties: 5,
+            mint_price: Uint128::from(100_000_000u128),
+            total_tiles: 10_000,
+            pixels_per_tile: 100,
         },
     };
 
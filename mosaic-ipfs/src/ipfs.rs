@@ -1,28 +1,14 @@
 use anyhow::Result;
 use ipfs_api_backend_hyper::{IpfsApi, IpfsClient, TryFromUri};
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{debug, error};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PixelMetadata {
-    pub color: Color,
-    pub version: u32,
-    pub previous_hash: Option<String>,
-    pub timestamp: u64,
-    pub expiration: Option<u64>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Color {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
-}
+// Metadata encoding and hashing are shared with the contracts
+pub use mosaic_types::{Color, PixelMetadata};
 
 pub struct IpfsService {
     client: IpfsClient,
-    cache: HashMap<String, String>, // pixel_id -> ipfs_hash
+    cache: HashMap<String, String>, // `PixelId` key, e.g. canvas:42 -> ipfs_hash
 }
 
 impl IpfsService {
@@ -64,11 +50,8 @@ impl IpfsService {
         let metadata = self.get_metadata(ipfs_hash).await?;
         
         // Calculate hash
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(b"pixel_v1_");
-        hasher.update(serde_json::to_vec(&metadata)?);
-        let calculated_hash = hex::encode(hasher.finalize());
-        
+        let calculated_hash = metadata.hash()?;
+
         Ok(calculated_hash == expected_hash)
     }
 
@@ -1,13 +1,13 @@
 use anyhow::Result;
 use tendermint_rpc::{Client, HttpClient, SubscriptionClient, WebSocketClient};
 use tendermint_rpc::query::EventType;
-use tendermint_rpc::event::Event;
+use tendermint_rpc::event::EventData;
+use tendermint::abci::Event;
 use std::collections::HashMap;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+use mosaic_types::{PixelId, PIXEL_UPDATE_EVENT_VERSION, WASM_EVENT_TYPE_PIXEL_UPDATE};
 use crate::ipfs::{IpfsService, PixelMetadata, Color};
 
-const EVENT_TYPE_PIXEL_UPDATE: &str = "pixel_update";
-
 pub struct ChainListener {
     rpc_client: HttpClient,
     ws_client: Option<WebSocketClient>,
@@ -60,7 +60,13 @@ impl ChainListener {
 
         while let Some(event) = subscription.next().await {
             match event {
-                Ok(event) => self.handle_event(event).await?,
+                Ok(event) => {
+                    if let EventData::Tx { tx_result } = event.data {
+                        for event in tx_result.result.events {
+                            self.handle_event(event).await?;
+                        }
+                    }
+                }
                 Err(e) => error!("Error receiving event: {}", e),
             }
         }
@@ -99,7 +105,7 @@ impl ChainListener {
     }
 
     async fn handle_event(&mut self, event: Event) -> Result<()> {
-        if event.type_str != EVENT_TYPE_PIXEL_UPDATE {
+        if event.kind != WASM_EVENT_TYPE_PIXEL_UPDATE {
             return Ok(());
         }
 
@@ -108,15 +114,31 @@ impl ChainListener {
             .map(|attr| (attr.key, attr.value))
             .collect();
 
+        // Ignore events emitted by other contracts in the same transaction
+        if attrs.get("_contract_address") != Some(&self.contract_address) {
+            debug!("Skipping pixel update from another contract");
+            return Ok(());
+        }
+
+        let event_version = attrs.get("event_version").map(String::as_str);
+        if event_version != Some(PIXEL_UPDATE_EVENT_VERSION) {
+            warn!("Skipping pixel update with unsupported event version {:?}", event_version);
+            return Ok(());
+        }
+
         // Extract metadata
         let metadata = self.extract_metadata(&attrs)?;
-        let pixel_id = attrs.get("pixel_id")
-            .ok_or_else(|| anyhow::anyhow!("No pixel_id in event"))?;
+        // The canvas and mosaic contracts number pixels differently, so the
+        // cache key names the numbering
+        let pixel_id = attrs.iter()
+            .find_map(|(key, value)| PixelId::from_attribute(key, value))
+            .ok_or_else(|| anyhow::anyhow!("No pixel_id or tile_pixel_id in event"))?
+            .to_string();
         let metadata_hash = attrs.get("metadata_hash")
             .ok_or_else(|| anyhow::anyhow!("No metadata_hash in event"))?;
 
         // Store in IPFS
-        let ipfs_hash = self.ipfs.store_metadata(pixel_id, &metadata).await?;
+        let ipfs_hash = self.ipfs.store_metadata(&pixel_id, &metadata).await?;
         
         // Verify hash
         if !self.ipfs.verify_metadata(&ipfs_hash, metadata_hash).await? {
@@ -0,0 +1,115 @@
+use alloc::string::{String, ToString};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+// Default canvas: 10,000 x 10,000 pixels split into 10x10 tiles
+pub const DEFAULT_CANVAS_WIDTH: u32 = 10_000;
+pub const DEFAULT_CANVAS_HEIGHT: u32 = 10_000;
+pub const DEFAULT_TILE_SIZE: u32 = 10;
+
+/// Dimensions of the shared canvas.
+/// Tiles are numbered row-major from the top-left corner and pixels are
+/// numbered row-major inside their tile.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Canvas {
+    /// Width of the canvas in pixels
+    pub width: u32,
+    /// Height of the canvas in pixels
+    pub height: u32,
+    /// Width and height of a tile in pixels
+    pub tile_size: u32,
+}
+
+/// Location of a canvas pixel inside the tile grid
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct PixelPosition {
+    /// Tile the pixel belongs to, also its token ID
+    pub tile_id: u32,
+    /// Row-major index of the pixel within its tile
+    pub offset: u32,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Canvas {
+            width: DEFAULT_CANVAS_WIDTH,
+            height: DEFAULT_CANVAS_HEIGHT,
+            tile_size: DEFAULT_TILE_SIZE,
+        }
+    }
+}
+
+impl Canvas {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.width == 0 || self.height == 0 || self.tile_size == 0 {
+            return Err(Error::InvalidCanvas("Dimensions must be greater than zero"));
+        }
+        if self.width % self.tile_size != 0 || self.height % self.tile_size != 0 {
+            return Err(Error::InvalidCanvas("Dimensions must be a multiple of the tile size"));
+        }
+        if self.pixels_per_tile().is_none() || self.total_tiles().is_none() {
+            return Err(Error::InvalidCanvas("Canvas is too large"));
+        }
+        Ok(())
+    }
+
+    pub fn tiles_per_row(&self) -> u32 {
+        self.width / self.tile_size
+    }
+
+    pub fn tiles_per_column(&self) -> u32 {
+        self.height / self.tile_size
+    }
+
+    pub fn pixels_per_tile(&self) -> Option<u32> {
+        self.tile_size.checked_mul(self.tile_size)
+    }
+
+    pub fn total_tiles(&self) -> Option<u32> {
+        self.tiles_per_row().checked_mul(self.tiles_per_column())
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// Row-major index of a pixel on the whole canvas
+    pub fn pixel_id(&self, x: u32, y: u32) -> u64 {
+        y as u64 * self.width as u64 + x as u64
+    }
+
+    /// Maps global canvas coordinates to a tile and an offset inside it
+    pub fn position(&self, x: u32, y: u32) -> Option<PixelPosition> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        Some(PixelPosition {
+            tile_id: (y / self.tile_size) * self.tiles_per_row() + x / self.tile_size,
+            offset: (y % self.tile_size) * self.tile_size + x % self.tile_size,
+        })
+    }
+
+    /// Maps a tile and an offset inside it back to global canvas coordinates
+    pub fn coordinates(&self, position: PixelPosition) -> Option<(u32, u32)> {
+        if position.tile_id >= self.total_tiles()? || position.offset >= self.pixels_per_tile()? {
+            return None;
+        }
+        let x = (position.tile_id % self.tiles_per_row()) * self.tile_size
+            + position.offset % self.tile_size;
+        let y = (position.tile_id / self.tiles_per_row()) * self.tile_size
+            + position.offset / self.tile_size;
+        Some((x, y))
+    }
+}
+
+impl PixelPosition {
+    pub fn token_id(&self) -> String {
+        self.tile_id.to_string()
+    }
+}
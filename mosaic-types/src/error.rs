@@ -0,0 +1,33 @@
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The canvas dimensions are unusable
+    InvalidCanvas(&'static str),
+    /// The pixel does not belong to the tile or canvas
+    PixelOutOfRange,
+    /// The pixel has been painted more than `u32::MAX` times
+    VersionOverflow,
+    /// Pixel metadata could not be encoded
+    Encoding,
+    /// A previous hash is not a hex encoded SHA-256 digest
+    InvalidPreviousHash,
+    /// A packed tile is malformed
+    Decoding(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidCanvas(reason) => write!(f, "Invalid canvas: {reason}"),
+            Error::PixelOutOfRange => write!(f, "Pixel is out of range"),
+            Error::VersionOverflow => write!(f, "Pixel version overflow"),
+            Error::Encoding => write!(f, "Pixel metadata could not be encoded"),
+            Error::InvalidPreviousHash => write!(f, "Invalid previous hash"),
+            Error::Decoding(reason) => write!(f, "Invalid packed tile: {reason}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -0,0 +1,81 @@
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{Error, PixelMetadata};
+
+/// Type of the event emitted for every painted pixel.
+/// CosmWasm prefixes it with `wasm-`, so indexers see `wasm-pixel_update`.
+pub const EVENT_TYPE_PIXEL_UPDATE: &str = "pixel_update";
+
+/// `EVENT_TYPE_PIXEL_UPDATE` as indexers and the IPFS listener see it
+pub const WASM_EVENT_TYPE_PIXEL_UPDATE: &str = "wasm-pixel_update";
+
+/// Version of the `pixel_update` attribute schema, bumped on breaking changes
+pub const PIXEL_UPDATE_EVENT_VERSION: &str = "1";
+
+/// A pixel as numbered by the contract that painted it. The numberings overlap,
+/// so each is announced under its own attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelId {
+    /// `y * width + x` on the canvas contract, announced as `pixel_id`
+    Canvas(u64),
+    /// `tile_id * pixels_per_tile + offset` on the mosaic contract, announced as `tile_pixel_id`
+    Tile(u32),
+}
+
+impl PixelId {
+    /// Attribute announcing the pixel in a `pixel_update` event
+    pub fn attribute(&self) -> (&'static str, String) {
+        match self {
+            PixelId::Canvas(id) => ("pixel_id", id.to_string()),
+            PixelId::Tile(id) => ("tile_pixel_id", id.to_string()),
+        }
+    }
+
+    /// Reads the pixel back from a `pixel_update` attribute, if it is one of its IDs
+    pub fn from_attribute(key: &str, value: &str) -> Option<Self> {
+        match key {
+            "pixel_id" => value.parse().ok().map(PixelId::Canvas),
+            "tile_pixel_id" => value.parse().ok().map(PixelId::Tile),
+            _ => None,
+        }
+    }
+}
+
+/// Unique across both numberings, e.g. `canvas:42` or `tile:42`
+impl fmt::Display for PixelId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PixelId::Canvas(id) => write!(f, "canvas:{id}"),
+            PixelId::Tile(id) => write!(f, "tile:{id}"),
+        }
+    }
+}
+
+/// Attributes of the `pixel_update` event announcing a new version of a pixel
+pub fn pixel_update_attributes(
+    pixel_id: PixelId,
+    metadata: &PixelMetadata,
+) -> Result<Vec<(&'static str, String)>, Error> {
+    let mut attributes = vec![
+        ("event_version", PIXEL_UPDATE_EVENT_VERSION.to_string()),
+        pixel_id.attribute(),
+        ("color_r", metadata.color.r.to_string()),
+        ("color_g", metadata.color.g.to_string()),
+        ("color_b", metadata.color.b.to_string()),
+        ("version", metadata.version.to_string()),
+        ("timestamp", metadata.timestamp.to_string()),
+        ("metadata_hash", metadata.hash()?),
+    ];
+    if let Some(expiration) = metadata.expiration {
+        attributes.push(("expiration", expiration.to_string()));
+    }
+    if let Some(previous_hash) = &metadata.previous_hash {
+        attributes.push(("previous_hash", previous_hash.clone()));
+    }
+    Ok(attributes)
+}
@@ -0,0 +1,27 @@
+//! Pixel, tile and canvas data shared by the mosaic contracts and the IPFS
+//! service, so that every component encodes and hashes pixels the same way.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod canvas;
+mod error;
+mod event;
+mod packed;
+mod pixel;
+mod tile;
+
+pub use crate::canvas::{
+    Canvas, PixelPosition, DEFAULT_CANVAS_HEIGHT, DEFAULT_CANVAS_WIDTH, DEFAULT_TILE_SIZE,
+};
+pub use crate::error::Error;
+pub use crate::event::{
+    pixel_update_attributes, PixelId, EVENT_TYPE_PIXEL_UPDATE, PIXEL_UPDATE_EVENT_VERSION,
+    WASM_EVENT_TYPE_PIXEL_UPDATE,
+};
+pub use crate::pixel::{Color, Pixel, PixelMetadata, METADATA_HASH_PREFIX};
+pub use crate::tile::{get_pixel_index, is_pixel_in_tile, Tile};
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,168 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::pixel::to_hex;
+use crate::{Error, Pixel, Tile};
+
+// Leading byte of every packed tile
+const TILE_ENCODING_VERSION: u8 = 1;
+// Pixel flags of the packed layout
+const FLAG_RENTER: u8 = 1;
+const FLAG_PREVIOUS_HASH: u8 = 1 << 1;
+const FLAG_PALETTE_INDEX: u8 = 1 << 2;
+// Length of a sha256 metadata hash
+const HASH_LENGTH: usize = 32;
+
+impl Tile {
+    /// Packs the tile as the encoding version and the pixel count, then for every
+    /// pixel a flags byte, its color bytes, varint expiration, version and update time, and
+    /// the raw previous hash and renter when flagged. Colors found in `palette` are stored
+    /// as a single index byte. The tile ID is not stored, it is the key of the tile.
+    pub fn to_packed(&self, palette: Option<&[[u8; 3]]>) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::with_capacity(self.pixels.len() * 8 + 64);
+        data.push(TILE_ENCODING_VERSION);
+        write_varint(&mut data, self.pixels.len() as u64);
+        for pixel in &self.pixels {
+            let index = palette.and_then(|palette| palette.iter().position(|color| *color == pixel.color));
+            let mut flags = 0;
+            if index.is_some() {
+                flags |= FLAG_PALETTE_INDEX;
+            }
+            if pixel.renter.is_some() {
+                flags |= FLAG_RENTER;
+            }
+            if pixel.previous_hash.is_some() {
+                flags |= FLAG_PREVIOUS_HASH;
+            }
+            data.push(flags);
+            match index {
+                Some(index) => data.push(index as u8),
+                None => data.extend_from_slice(&pixel.color),
+            }
+            write_varint(&mut data, pixel.expiration);
+            write_varint(&mut data, pixel.version.into());
+            write_varint(&mut data, pixel.updated_at);
+            if let Some(previous_hash) = &pixel.previous_hash {
+                data.extend_from_slice(&from_hex(previous_hash).ok_or(Error::InvalidPreviousHash)?);
+            }
+            if let Some(renter) = &pixel.renter {
+                write_bytes(&mut data, renter.as_bytes());
+            }
+        }
+        Ok(data)
+    }
+
+    /// Unpacks tile `tile_id` written by `to_packed`, resolving palette indexes with `palette`
+    pub fn from_packed(tile_id: u32, data: &[u8], palette: Option<&[[u8; 3]]>) -> Result<Self, Error> {
+        let mut reader = Reader { data };
+        if reader.byte()? != TILE_ENCODING_VERSION {
+            return Err(Error::Decoding("unknown encoding version"));
+        }
+        let len = reader.varint()? as usize;
+        let mut pixels = Vec::with_capacity(len.min(data.len()));
+        for _ in 0..len {
+            let flags = reader.byte()?;
+            let mut color = [0; 3];
+            if flags & FLAG_PALETTE_INDEX != 0 {
+                let index = reader.byte()?;
+                color = *palette
+                    .and_then(|palette| palette.get(index as usize))
+                    .ok_or(Error::Decoding("unknown palette index"))?;
+            } else {
+                color.copy_from_slice(reader.bytes(3)?);
+            }
+            let expiration = reader.varint()?;
+            let version = u32::try_from(reader.varint()?).map_err(|_| Error::VersionOverflow)?;
+            let updated_at = reader.varint()?;
+            let previous_hash = if flags & FLAG_PREVIOUS_HASH != 0 {
+                Some(to_hex(reader.bytes(HASH_LENGTH)?))
+            } else {
+                None
+            };
+            let renter = if flags & FLAG_RENTER != 0 {
+                Some(reader.string()?)
+            } else {
+                None
+            };
+            pixels.push(Pixel {
+                color,
+                expiration,
+                renter,
+                version,
+                previous_hash,
+                updated_at,
+            });
+        }
+        if !reader.data.is_empty() {
+            return Err(Error::Decoding("trailing bytes"));
+        }
+        Ok(Tile { tile_id, pixels })
+    }
+}
+
+fn write_varint(data: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        data.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+fn write_bytes(data: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(data, bytes.len() as u64);
+    data.extend_from_slice(bytes);
+}
+
+/// Raw bytes of a hex encoded metadata hash
+fn from_hex(hex: &str) -> Option<[u8; HASH_LENGTH]> {
+    let digit = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    let hex = hex.as_bytes();
+    if hex.len() != HASH_LENGTH * 2 {
+        return None;
+    }
+    let mut bytes = [0; HASH_LENGTH];
+    for (byte, pair) in bytes.iter_mut().zip(hex.chunks_exact(2)) {
+        *byte = digit(pair[0])? << 4 | digit(pair[1])?;
+    }
+    Some(bytes)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < len {
+            return Err(Error::Decoding("unexpected end of data"));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::Decoding("varint overflow"))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let len = self.varint()? as usize;
+        core::str::from_utf8(self.bytes(len)?)
+            .map(ToString::to_string)
+            .map_err(|_| Error::Decoding("invalid utf-8"))
+    }
+}
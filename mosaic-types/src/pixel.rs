@@ -0,0 +1,130 @@
+use alloc::string::String;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::Error;
+
+/// Domain separator of the pixel metadata hash
+pub const METADATA_HASH_PREFIX: &[u8] = b"pixel_v1_";
+
+/// An RGB color
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl From<[u8; 3]> for Color {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Color { r, g, b }
+    }
+}
+
+impl From<Color> for [u8; 3] {
+    fn from(color: Color) -> Self {
+        [color.r, color.g, color.b]
+    }
+}
+
+/// A pixel as stored on chain
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Pixel {
+    /// RGB color values
+    pub color: [u8; 3],
+    /// Time in seconds until which the color is locked
+    pub expiration: u64,
+    /// Address that rented the pixel, `None` when painted by the tile owner
+    pub renter: Option<String>,
+    /// Number of times the pixel has been painted
+    pub version: u32,
+    /// Metadata hash of the previous version, `None` until painted twice
+    pub previous_hash: Option<String>,
+    /// Time in seconds of the latest update
+    pub updated_at: u64,
+}
+
+impl Pixel {
+    /// A pixel can be painted once its current color has expired
+    pub fn is_available(&self, now: u64) -> bool {
+        self.expiration <= now
+    }
+
+    /// Metadata of the current version, `None` for unpainted pixels
+    pub fn metadata(&self) -> Option<PixelMetadata> {
+        if self.version == 0 {
+            return None;
+        }
+        Some(PixelMetadata {
+            color: self.color.into(),
+            version: self.version,
+            previous_hash: self.previous_hash.clone(),
+            timestamp: self.updated_at,
+            expiration: Some(self.expiration),
+        })
+    }
+
+    /// Paints the pixel at time `now` as a new version chained to the current one
+    pub fn paint(
+        &mut self,
+        color: [u8; 3],
+        expiration: u64,
+        renter: Option<String>,
+        now: u64,
+    ) -> Result<(), Error> {
+        let previous_hash = self.metadata().map(|metadata| metadata.hash()).transpose()?;
+        let version = self.version.checked_add(1).ok_or(Error::VersionOverflow)?;
+        *self = Pixel {
+            color,
+            expiration,
+            renter,
+            version,
+            previous_hash,
+            updated_at: now,
+        };
+        Ok(())
+    }
+}
+
+/// Metadata of a single pixel version as pinned to IPFS and announced in
+/// `pixel_update` events.
+/// Field order matters: the metadata hash is computed over its JSON encoding.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct PixelMetadata {
+    pub color: Color,
+    /// Number of times the pixel has been painted, starting at 1
+    pub version: u32,
+    /// Metadata hash of the previous version of the pixel
+    pub previous_hash: Option<String>,
+    /// Time in seconds of the update
+    pub timestamp: u64,
+    pub expiration: Option<u64>,
+}
+
+impl PixelMetadata {
+    /// Hex encoded SHA-256 of the metadata JSON, prefixed with `pixel_v1_`
+    pub fn hash(&self) -> Result<String, Error> {
+        let json = serde_json::to_vec(self).map_err(|_| Error::Encoding)?;
+        let mut hasher = Sha256::new();
+        hasher.update(METADATA_HASH_PREFIX);
+        hasher.update(json);
+        Ok(to_hex(&hasher.finalize()))
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        hex.push(DIGITS[(byte >> 4) as usize] as char);
+        hex.push(DIGITS[(byte & 0xf) as usize] as char);
+    }
+    hex
+}
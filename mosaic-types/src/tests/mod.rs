@@ -0,0 +1,127 @@
+use alloc::{format, string::{String, ToString}};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    pixel_update_attributes, Canvas, Color, Error, Pixel, PixelId, PixelMetadata, PixelPosition, Tile,
+    EVENT_TYPE_PIXEL_UPDATE, WASM_EVENT_TYPE_PIXEL_UPDATE,
+};
+
+#[test]
+fn canvas_positions() {
+    let canvas = Canvas::default();
+    canvas.validate().unwrap();
+
+    let position = canvas.position(25, 13).unwrap();
+    assert_eq!(position, PixelPosition { tile_id: 1002, offset: 35 });
+    assert_eq!(canvas.coordinates(position), Some((25, 13)));
+    assert_eq!(canvas.position(canvas.width, 0), None);
+
+    let canvas = Canvas { width: 15, height: 10, tile_size: 10 };
+    assert!(matches!(canvas.validate(), Err(Error::InvalidCanvas(_))));
+}
+
+#[test]
+fn metadata_hash_is_prefixed_json() {
+    let metadata = PixelMetadata {
+        color: Color { r: 1, g: 2, b: 3 },
+        version: 1,
+        previous_hash: None,
+        timestamp: 10,
+        expiration: Some(20),
+    };
+
+    let json = br#"{"color":{"r":1,"g":2,"b":3},"version":1,"previous_hash":null,"timestamp":10,"expiration":20}"#;
+    let mut hasher = Sha256::new();
+    hasher.update(b"pixel_v1_");
+    hasher.update(json);
+    let expected: String = hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect();
+
+    assert_eq!(metadata.hash().unwrap(), expected);
+}
+
+#[test]
+fn paint_chains_versions() {
+    let mut pixel = Pixel::default();
+    assert_eq!(pixel.metadata(), None);
+
+    pixel.paint([1, 2, 3], 20, None, 10).unwrap();
+    let first = pixel.metadata().unwrap();
+    assert_eq!(first.version, 1);
+    assert_eq!(first.previous_hash, None);
+
+    pixel.paint([4, 5, 6], 40, Some("renter".to_string()), 30).unwrap();
+    let second = pixel.metadata().unwrap();
+    assert_eq!(second.version, 2);
+    assert_eq!(second.previous_hash, Some(first.hash().unwrap()));
+    assert_eq!(pixel.renter.as_deref(), Some("renter"));
+}
+
+#[test]
+fn tile_pixels() {
+    let mut tile = Tile::new(2, 100);
+    assert_eq!(tile.get_pixel(200).unwrap().color, [255, 255, 255]);
+    assert_eq!(tile.get_pixel(300), Err(Error::PixelOutOfRange));
+
+    tile.update_pixel(205, [9, 9, 9], 50, 10).unwrap();
+    assert_eq!(tile.get_pixel(205).unwrap().color, [9, 9, 9]);
+    assert_eq!(tile.update_pixel(199, [9, 9, 9], 50, 10), Err(Error::PixelOutOfRange));
+}
+
+#[test]
+fn tile_packing() {
+    let mut tile = Tile::new(7, 4);
+    tile.pixels[0].paint([1, 2, 3], 60, None, 10).unwrap();
+    tile.pixels[0].paint([4, 5, 6], 120, Some("renter".to_string()), 70).unwrap();
+    tile.pixels[1].expiration = u64::MAX;
+    assert!(tile.pixels[0].previous_hash.is_some());
+
+    // Every field survives the packed layout, palette colors take a single byte
+    let packed = tile.to_packed(None).unwrap();
+    assert_eq!(Tile::from_packed(7, &packed, None).unwrap(), tile);
+    let palette = [[255, 255, 255]];
+    let indexed = tile.to_packed(Some(&palette)).unwrap();
+    assert_eq!(indexed.len() + 6, packed.len());
+    assert_eq!(Tile::from_packed(7, &indexed, Some(&palette)).unwrap(), tile);
+    assert!(Tile::from_packed(7, &indexed, None).is_err());
+
+    assert!(Tile::from_packed(7, &packed[..packed.len() - 1], None).is_err());
+    assert!(Tile::from_packed(7, &[&packed[..], &[0]].concat(), None).is_err());
+    assert!(Tile::from_packed(7, &[&[2], &packed[1..]].concat(), None).is_err());
+    tile.pixels[2].previous_hash = Some("abc".to_string());
+    assert_eq!(tile.to_packed(None), Err(Error::InvalidPreviousHash));
+}
+
+#[test]
+fn pixel_update_event() {
+    assert_eq!(WASM_EVENT_TYPE_PIXEL_UPDATE, format!("wasm-{EVENT_TYPE_PIXEL_UPDATE}"));
+
+    let mut metadata = PixelMetadata {
+        color: Color { r: 1, g: 2, b: 3 },
+        version: 1,
+        previous_hash: None,
+        timestamp: 10,
+        expiration: None,
+    };
+    let keys = |metadata: &PixelMetadata| {
+        pixel_update_attributes(PixelId::Canvas(42), metadata).unwrap().into_iter().map(|(key, _)| key).collect::<alloc::vec::Vec<_>>()
+    };
+    let required = ["event_version", "pixel_id", "color_r", "color_g", "color_b", "version", "timestamp", "metadata_hash"];
+    assert_eq!(keys(&metadata), required);
+
+    // Optional fields are only announced when set
+    metadata.expiration = Some(60);
+    metadata.previous_hash = Some(metadata.hash().unwrap());
+    assert_eq!(keys(&metadata), [&required[..], &["expiration", "previous_hash"]].concat());
+    let attributes = pixel_update_attributes(PixelId::Canvas(42), &metadata).unwrap();
+    assert!(attributes.contains(&("pixel_id", "42".to_string())));
+    assert!(attributes.contains(&("metadata_hash", metadata.hash().unwrap())));
+
+    // Each contract's numbering has its own attribute and key
+    let attributes = pixel_update_attributes(PixelId::Tile(42), &metadata).unwrap();
+    assert_eq!(attributes[1], ("tile_pixel_id", "42".to_string()));
+    assert_eq!(PixelId::from_attribute("tile_pixel_id", "42"), Some(PixelId::Tile(42)));
+    assert_eq!(PixelId::from_attribute("pixel_id", "42"), Some(PixelId::Canvas(42)));
+    assert_eq!(PixelId::from_attribute("version", "42"), None);
+    assert_ne!(PixelId::Tile(42).to_string(), PixelId::Canvas(42).to_string());
+}
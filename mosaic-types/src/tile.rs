@@ -0,0 +1,91 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Pixel};
+
+/// A tile of pixels, numbered row-major from `tile_id * pixels_per_tile`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Tile {
+    /// Unique tile identifier
+    pub tile_id: u32,
+    /// Pixels of the tile
+    pub pixels: Vec<Pixel>,
+}
+
+impl Tile {
+    /// Create a new tile with default white pixels
+    pub fn new(tile_id: u32, pixels_per_tile: u32) -> Self {
+        let pixel = Pixel {
+            color: [255, 255, 255],
+            ..Pixel::default()
+        };
+        Self {
+            tile_id,
+            pixels: alloc::vec![pixel; pixels_per_tile as usize],
+        }
+    }
+
+    /// Number of pixels in the tile
+    pub fn pixels_per_tile(&self) -> u32 {
+        self.pixels.len() as u32
+    }
+
+    /// Get a pixel by its global pixel ID
+    pub fn get_pixel(&self, pixel_id: u32) -> Result<&Pixel, Error> {
+        if !is_pixel_in_tile(pixel_id, self.tile_id, self.pixels_per_tile()) {
+            return Err(Error::PixelOutOfRange);
+        }
+        Ok(&self.pixels[get_pixel_index(pixel_id, self.pixels_per_tile())])
+    }
+
+    /// Update a pixel's color and expiration at time `timestamp`,
+    /// chaining the new version to the previous one
+    pub fn update_pixel(
+        &mut self,
+        pixel_id: u32,
+        color: [u8; 3],
+        expiration: u64,
+        timestamp: u64,
+    ) -> Result<(), Error> {
+        if !is_pixel_in_tile(pixel_id, self.tile_id, self.pixels_per_tile()) {
+            return Err(Error::PixelOutOfRange);
+        }
+        let pixel_index = get_pixel_index(pixel_id, self.pixels_per_tile());
+        self.pixels[pixel_index].paint(color, expiration, None, timestamp)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Tile {
+    /// Convert the tile to bytes for storage
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+
+    /// Create a tile from bytes
+    pub fn from_bytes(data: &[u8]) -> Result<Self, bincode::Error> {
+        bincode::deserialize(data)
+    }
+
+    /// SHA-256 of the binary encoding of the tile
+    pub fn calculate_hash(&self) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        hasher.update(self.to_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// Check if a pixel belongs to a tile
+pub fn is_pixel_in_tile(pixel_id: u32, tile_id: u32, pixels_per_tile: u32) -> bool {
+    pixels_per_tile != 0 && pixel_id / pixels_per_tile == tile_id
+}
+
+/// Index of a pixel within its tile
+pub fn get_pixel_index(pixel_id: u32, pixels_per_tile: u32) -> usize {
+    (pixel_id % pixels_per_tile) as usize
+}
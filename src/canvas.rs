@@ -0,0 +1,4 @@
+// Canvas geometry is shared with the other mosaic components
+pub use mosaic_types::{
+    Canvas, PixelPosition, DEFAULT_CANVAS_HEIGHT, DEFAULT_CANVAS_WIDTH, DEFAULT_TILE_SIZE,
+};
@@ -1,32 +1,51 @@
-use cw721_base::state::TokenInfo;
+use std::collections::{btree_map::Entry, BTreeMap};
 use url::Url;
 
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, ContractInfoResponse, Decimal, Deps, DepsMut, Empty, Env, Event,
-    MessageInfo, Response, StdError, StdResult, Storage, Timestamp, WasmQuery,
+    coins, to_json_binary, Addr, BankMsg, Binary, Coin, ContractInfoResponse, Decimal, Deps,
+    DepsMut, Env, Event, MessageInfo, Order, Response, StdError, StdResult, Storage, Timestamp,
+    Uint128, WasmQuery,
 };
 
-use cw721::{ContractInfoResponse as CW721ContractInfoResponse, Cw721Execute};
-use cw_utils::nonpayable;
+use cw721::ContractInfoResponse as CW721ContractInfoResponse;
+use cw721_base::state::TokenInfo;
+use cw_storage_plus::Bound;
+use cw_utils::{nonpayable, Expiration};
 use serde::{de::DeserializeOwned, Serialize};
 use cw_ownable::{assert_owner, get_ownership};
 
-use sg721::{
-    CollectionInfo, ExecuteMsg, InstantiateMsg, RoyaltyInfo, RoyaltyInfoResponse,
-    UpdateCollectionInfoMsg,
-};
+use sg721::{CollectionInfo, RoyaltyInfo, RoyaltyInfoResponse, UpdateCollectionInfoMsg};
 
-use crate::msg::{CollectionInfoResponse, CustomExecuteMsg, QueryMsg};
+use crate::canvas::{Canvas, PixelPosition};
+use crate::events::{pixel_update_event, PixelId};
+use crate::msg::{
+    BaseImageResponse, CollectionInfoResponse, ColoringGrant, ColoringRightsResponse,
+    CustomExecuteMsg, ExpiryPolicy, InstantiateMsg, PaintRun, PauseStatus, PixelColor,
+    PixelResponse, PixelUpdate, ProtectedPixelsResponse, QueryMsg, RateLimit, TileResponse,
+    TilesResponse,
+};
+use crate::palette::{resolve_color, validate_palette};
+use crate::rate_limit::{
+    consume_rate_limit, cooldown_until, query_paint_allowance, validate_rate_limit,
+};
 use crate::{ContractError, Sg721Contract};
 use crate::state::{
-    Pixel, Tile, COLLECTION_INFO, FROZEN_COLLECTION_INFO, ROYALTY_UPDATED_AT, TILES,
+    ColoringRight, Pixel, Tile, BASE_IMAGES, CANVAS, COLLECTION_INFO, COLORING_RIGHTS,
+    EXPIRY_POLICY, FROZEN_COLLECTION_INFO, PALETTE, PAUSE_STATUS, PROTECTED_PIXELS, RATE_LIMIT,
+    RENTAL_PRICE, ROYALTY_UPDATED_AT, TILES,
 };
 
 // Constants
 const MAX_DESCRIPTION_LENGTH: u32 = 512;
 const MAX_SHARE_DELTA_PCT: u64 = 2;
 const MAX_ROYALTY_SHARE_PCT: u64 = 10;
-const PIXELS_PER_TILE: u32 = 100; // 10x10 grid
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+const MAX_BATCH_SIZE: usize = 500;
+const SECONDS_PER_DAY: u64 = 86_400;
+// 0.1 STARS per pixel per day
+pub(crate) const DEFAULT_RENTAL_PRICE: u128 = 100_000;
+pub(crate) const DEFAULT_RENTAL_DENOM: &str = "ustars";
 
 impl<'a, T> Sg721Contract<'a, T>
 where
@@ -93,8 +112,26 @@ where
 
         COLLECTION_INFO.save(deps.storage, &collection_info)?;
         FROZEN_COLLECTION_INFO.save(deps.storage, &false)?;
+        CANVAS.save(deps.storage, &Canvas::default())?;
+        RENTAL_PRICE.save(
+            deps.storage,
+            &Coin {
+                denom: DEFAULT_RENTAL_DENOM.to_string(),
+                amount: Uint128::from(DEFAULT_RENTAL_PRICE),
+            },
+        )?;
         ROYALTY_UPDATED_AT.save(deps.storage, &env.block.time)?;
 
+        if let Some(palette) = msg.palette {
+            validate_palette(&palette)?;
+            PALETTE.save(deps.storage, &palette)?;
+        }
+
+        if let Some(rate_limit) = msg.rate_limit {
+            validate_rate_limit(&rate_limit)?;
+            RATE_LIMIT.save(deps.storage, &rate_limit)?;
+        }
+
         Ok(Response::new()
             .add_attribute("action", "instantiate")
             .add_attribute("collection_name", info.name)
@@ -111,10 +148,13 @@ where
         info: MessageInfo,
         msg: sg721::ExecuteMsg<T, CustomExecuteMsg>,
     ) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage, &msg)?;
+
         match msg {
             sg721::ExecuteMsg::Extension { msg } => match msg {
                 CustomExecuteMsg::SetPixelColor {
-                    pixel_id,
+                    x,
+                    y,
                     current_tile_metadata,
                     color,
                     expiration,
@@ -122,11 +162,60 @@ where
                     deps,
                     env,
                     info,
-                    pixel_id,
+                    x,
+                    y,
                     current_tile_metadata,
                     color,
                     expiration,
                 ),
+                CustomExecuteMsg::SetPixelColors { updates } => {
+                    self.execute_set_pixel_colors(deps, env, info, updates)
+                }
+                CustomExecuteMsg::FillRect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    color,
+                    expiration,
+                } => self.execute_fill_rect(deps, env, info, x, y, width, height, color, expiration),
+                CustomExecuteMsg::PaintRuns {
+                    x,
+                    y,
+                    width,
+                    runs,
+                    expiration,
+                } => self.execute_paint_runs(deps, env, info, x, y, width, runs, expiration),
+                CustomExecuteMsg::UpdateCanvas { canvas } => {
+                    self.update_canvas(deps, env, info, canvas)
+                }
+                CustomExecuteMsg::UpdateRentalPrice { price } => {
+                    self.update_rental_price(deps, env, info, price)
+                }
+                CustomExecuteMsg::UpdatePalette { palette } => {
+                    self.update_palette(deps, env, info, palette)
+                }
+                CustomExecuteMsg::UpdateRateLimit { rate_limit } => {
+                    self.update_rate_limit(deps, env, info, rate_limit)
+                }
+                CustomExecuteMsg::GrantColoringRights { token_id, grantee, expires } => {
+                    self.grant_coloring_rights(deps, env, info, token_id, grantee, expires)
+                }
+                CustomExecuteMsg::RevokeColoringRights { token_id, grantee } => {
+                    self.revoke_coloring_rights(deps, env, info, token_id, grantee)
+                }
+                CustomExecuteMsg::UpdateProtectedPixels { token_id, mask } => {
+                    self.update_protected_pixels(deps, env, info, token_id, mask)
+                }
+                CustomExecuteMsg::SetBaseImage { token_id, colors } => {
+                    self.set_base_image(deps, env, info, token_id, colors)
+                }
+                CustomExecuteMsg::UpdateExpiryPolicy { policy } => {
+                    self.update_expiry_policy(deps, env, info, policy)
+                }
+                CustomExecuteMsg::UpdatePauseStatus { status } => {
+                    self.update_pause_status(deps, env, info, status)
+                }
                 CustomExecuteMsg::UpdateCollectionInfo { collection_info } => {
                     self.update_collection_info(deps, env, info, collection_info)
                 }
@@ -143,7 +232,7 @@ where
             sg721::ExecuteMsg::UpdateStartTradingTime(start_time) => {
                 self.update_start_trading_time(deps, env, info, start_time)
             }
-            sg721::ExecuteMsg::FreezeCollectionInfo {} => {
+            sg721::ExecuteMsg::FreezeCollectionInfo => {
                 self.freeze_collection_info(deps, env, info)
             }
             sg721::ExecuteMsg::Burn { .. } => {
@@ -180,70 +269,255 @@ where
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn execute_set_pixel_color(
         &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        pixel_id: u32,
-        current_tile_metadata: Binary,
+        x: u32,
+        y: u32,
+        _current_tile_metadata: Binary,
+        color: PixelColor,
+        expiration: u64,
+    ) -> Result<Response, ContractError> {
+        let canvas = CANVAS.load(deps.storage)?;
+        let palette = PALETTE.may_load(deps.storage)?;
+        let color = resolve_color(palette.as_deref(), color)?;
+        let pixel_cooldown = pixel_cooldown(deps.storage)?;
+        let update = PixelUpdate {
+            x,
+            y,
+            color,
+            expiration,
+        };
+
+        let mut tiles = BTreeMap::new();
+        let mut rents = BTreeMap::new();
+        let token_id = self.paint_pixel(
+            deps.storage,
+            &env,
+            &info.sender,
+            &canvas,
+            &mut tiles,
+            &mut rents,
+            &update,
+            pixel_cooldown,
+        )?;
+        consume_rate_limit(deps.storage, &env, &info.sender, 1)?;
+        let (rent, messages) = collect_rent(deps.storage, &info, rents)?;
+
+        let event = pixel_event(&canvas, &tiles, &update)?;
+
+        // Save tile
+        for (token_id, (_, tile)) in tiles {
+            TILES.save(deps.storage, token_id, &tile)?;
+        }
+
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_event(event)
+            .add_attribute("action", "set_pixel_color")
+            .add_attribute("token_id", token_id)
+            .add_attribute("x", x.to_string())
+            .add_attribute("y", y.to_string())
+            .add_attribute("color", format!("{:?}", color))
+            .add_attribute("expiration", expiration.to_string())
+            .add_attribute("rent", rent))
+    }
+
+    pub fn execute_set_pixel_colors(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        updates: Vec<PixelUpdate>,
+    ) -> Result<Response, ContractError> {
+        self.paint_batch(deps, env, info, "set_pixel_colors", updates)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_fill_rect(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
         color: [u8; 3],
         expiration: u64,
     ) -> Result<Response, ContractError> {
-        // Validate pixel ID
-        if pixel_id >= PIXELS_PER_TILE {
-            return Err(ContractError::InvalidPixelId {});
+        if width == 0 || height == 0 {
+            return Err(ContractError::InvalidPixelUpdate("Rectangle is empty".to_string()));
+        }
+        if width as u64 * height as u64 > MAX_BATCH_SIZE as u64 {
+            return Err(ContractError::InvalidPixelUpdate(format!(
+                "Cannot update more than {MAX_BATCH_SIZE} pixels at once"
+            )));
         }
 
-        // Validate expiration
-        if expiration <= env.block.time.seconds() {
-            return Err(ContractError::InvalidExpiration {});
+        let x_end = x.checked_add(width).ok_or(ContractError::PixelOutOfRange {})?;
+        let y_end = y.checked_add(height).ok_or(ContractError::PixelOutOfRange {})?;
+        let updates = (y..y_end)
+            .flat_map(|row| {
+                (x..x_end).map(move |col| PixelUpdate {
+                    x: col,
+                    y: row,
+                    color,
+                    expiration,
+                })
+            })
+            .collect();
+
+        self.paint_batch(deps, env, info, "fill_rect", updates)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_paint_runs(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        x: u32,
+        y: u32,
+        width: u32,
+        runs: Vec<PaintRun>,
+        expiration: u64,
+    ) -> Result<Response, ContractError> {
+        if width == 0 {
+            return Err(ContractError::InvalidPixelUpdate("Run width is zero".to_string()));
         }
 
-        // Calculate token ID from pixel ID
-        let token_id = (pixel_id / PIXELS_PER_TILE).to_string();
+        // Runs fill the `width` wide area row by row, starting at (x, y)
+        let mut updates = vec![];
+        let mut index: u64 = 0;
+        for run in runs {
+            let Some(color) = run.color else {
+                index += run.length as u64;
+                continue;
+            };
+            if updates.len() + run.length as usize > MAX_BATCH_SIZE {
+                return Err(ContractError::InvalidPixelUpdate(format!(
+                    "Cannot update more than {MAX_BATCH_SIZE} pixels at once"
+                )));
+            }
+            for _ in 0..run.length {
+                let col = x as u64 + index % width as u64;
+                let row = y as u64 + index / width as u64;
+                updates.push(PixelUpdate {
+                    x: u32::try_from(col).map_err(|_| ContractError::PixelOutOfRange {})?,
+                    y: u32::try_from(row).map_err(|_| ContractError::PixelOutOfRange {})?,
+                    color,
+                    expiration,
+                });
+                index += 1;
+            }
+        }
 
-        // Load tile
-        let mut tile = TILES.may_load(deps.storage, token_id.clone())?
-            .unwrap_or_else(|| Tile {
-                owner: info.sender.clone(),
-                pixels: vec![Pixel {
-                    color: [0, 0, 0],
-                    expiration: 0,
-                }; PIXELS_PER_TILE as usize],
-            });
+        self.paint_batch(deps, env, info, "paint_runs", updates)
+    }
 
-        // Verify ownership
-        if tile.owner != info.sender {
-            return Err(ContractError::Unauthorized {});
+    /// Applies a set of pixel updates atomically, loading and saving each
+    /// affected tile once
+    fn paint_batch(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        action: &str,
+        updates: Vec<PixelUpdate>,
+    ) -> Result<Response, ContractError> {
+        if updates.is_empty() {
+            return Err(ContractError::InvalidPixelUpdate("No pixel updates".to_string()));
+        }
+        if updates.len() > MAX_BATCH_SIZE {
+            return Err(ContractError::InvalidPixelUpdate(format!(
+                "Cannot update more than {MAX_BATCH_SIZE} pixels at once"
+            )));
         }
 
-        // Check pixel availability
-        let pixel_index = (pixel_id % PIXELS_PER_TILE) as usize;
-        if tile.pixels[pixel_index].expiration > env.block.time.seconds() {
-            return Err(ContractError::InvalidPixelUpdate("Pixel is not available".to_string()));
+        let canvas = CANVAS.load(deps.storage)?;
+        let pixel_cooldown = pixel_cooldown(deps.storage)?;
+        let palette = PALETTE.may_load(deps.storage)?;
+        for update in &updates {
+            resolve_color(palette.as_deref(), PixelColor::Rgb(update.color))?;
         }
 
-        // Update pixel
-        tile.pixels[pixel_index] = Pixel {
-            color,
-            expiration,
-        };
+        // Every update is applied to the in-memory tiles first so that a single
+        // locked pixel rejects the whole batch
+        let mut tiles = BTreeMap::new();
+        let mut rents = BTreeMap::new();
+        for update in &updates {
+            self.paint_pixel(
+                deps.storage,
+                &env,
+                &info.sender,
+                &canvas,
+                &mut tiles,
+                &mut rents,
+                update,
+                pixel_cooldown,
+            )?;
+        }
+        consume_rate_limit(deps.storage, &env, &info.sender, updates.len() as u32)?;
+        let (rent, messages) = collect_rent(deps.storage, &info, rents)?;
 
-        // Save tile
-        TILES.save(deps.storage, token_id, &tile)?;
+        let events = updates
+            .iter()
+            .map(|update| pixel_event(&canvas, &tiles, update))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let tile_count = tiles.len();
+        let token_ids = tiles.keys().cloned().collect::<Vec<_>>().join(",");
+        for (token_id, (_, tile)) in tiles {
+            TILES.save(deps.storage, token_id, &tile)?;
+        }
 
         Ok(Response::new()
-            .add_attribute("action", "set_pixel_color")
-            .add_attribute("pixel_id", pixel_id.to_string())
-            .add_attribute("color", format!("{:?}", color))
-            .add_attribute("expiration", expiration.to_string()))
+            .add_messages(messages)
+            .add_events(events)
+            .add_attribute("action", action)
+            .add_attribute("pixel_count", updates.len().to_string())
+            .add_attribute("tile_count", tile_count.to_string())
+            .add_attribute("token_ids", token_ids)
+            .add_attribute("rent", rent))
     }
 
     pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         match msg {
             QueryMsg::CollectionInfo {} => to_json_binary(&self.query_collection_info(deps)?),
             QueryMsg::Ownership {} => self.parent.query(deps, env, cw721_base::QueryMsg::Ownership {}),
+            QueryMsg::Canvas {} => to_json_binary(&CANVAS.load(deps.storage)?),
+            QueryMsg::RentalPrice {} => to_json_binary(&RENTAL_PRICE.load(deps.storage)?),
+            QueryMsg::Palette {} => to_json_binary(&PALETTE.may_load(deps.storage)?),
+            QueryMsg::PauseStatus {} => {
+                to_json_binary(&PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default())
+            }
+            QueryMsg::RateLimit {} => to_json_binary(&RATE_LIMIT.may_load(deps.storage)?),
+            QueryMsg::PaintAllowance { address } => {
+                to_json_binary(&query_paint_allowance(deps, env, address)?)
+            }
+            QueryMsg::Pixel { x, y } => to_json_binary(&self.query_pixel(deps, env, x, y)?),
+            QueryMsg::Tile { token_id } => to_json_binary(&self.query_tile(deps, env, token_id)?),
+            QueryMsg::Tiles { start_after, limit } => {
+                to_json_binary(&self.query_tiles(deps, env, start_after, limit)?)
+            }
+            QueryMsg::ProtectedPixels { token_id } => {
+                let mask = PROTECTED_PIXELS.may_load(deps.storage, &token_id)?.unwrap_or_default();
+                to_json_binary(&ProtectedPixelsResponse { mask })
+            }
+            QueryMsg::BaseImage { token_id } => {
+                let colors = BASE_IMAGES.may_load(deps.storage, &token_id)?.unwrap_or_default();
+                to_json_binary(&BaseImageResponse { colors })
+            }
+            QueryMsg::ExpiryPolicy {} => {
+                to_json_binary(&EXPIRY_POLICY.may_load(deps.storage)?.unwrap_or_default())
+            }
+            QueryMsg::ColoringRights { token_id, include_expired } => {
+                to_json_binary(&self.query_coloring_rights(deps, env, token_id, include_expired)?)
+            }
             _ => {
                 // Convert sg721 QueryMsg to cw721-base QueryMsg
                 let base_msg = match msg {
@@ -279,6 +553,473 @@ where
         }
     }
 
+    pub fn update_canvas(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        canvas: Canvas,
+    ) -> Result<Response, ContractError> {
+        assert_minter_owner(deps.storage, &info.sender)?;
+
+        // Resizing would remap every painted pixel
+        if !TILES.is_empty(deps.storage) {
+            return Err(ContractError::InvalidCanvas(
+                "Canvas cannot change once tiles are painted".to_string(),
+            ));
+        }
+        canvas.validate()?;
+        CANVAS.save(deps.storage, &canvas)?;
+
+        let event = Event::new("update_canvas")
+            .add_attribute("sender", info.sender)
+            .add_attribute("width", canvas.width.to_string())
+            .add_attribute("height", canvas.height.to_string())
+            .add_attribute("tile_size", canvas.tile_size.to_string());
+        Ok(Response::new().add_event(event))
+    }
+
+    pub fn update_rental_price(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        price: Coin,
+    ) -> Result<Response, ContractError> {
+        assert_minter_owner(deps.storage, &info.sender)?;
+        if price.denom.is_empty() {
+            return Err(ContractError::Std(StdError::generic_err("Rental denom cannot be empty")));
+        }
+        RENTAL_PRICE.save(deps.storage, &price)?;
+
+        let event = Event::new("update_rental_price")
+            .add_attribute("sender", info.sender)
+            .add_attribute("price", price.to_string());
+        Ok(Response::new().add_event(event))
+    }
+
+    pub fn update_palette(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        palette: Vec<[u8; 3]>,
+    ) -> Result<Response, ContractError> {
+        assert_minter_owner(deps.storage, &info.sender)?;
+        if FROZEN_COLLECTION_INFO.load(deps.storage)? {
+            return Err(ContractError::CollectionInfoFrozen {});
+        }
+
+        // The palette mode is chosen at instantiation
+        let current = PALETTE
+            .may_load(deps.storage)?
+            .ok_or(ContractError::PaletteDisabled {})?;
+        validate_palette(&palette)?;
+        // Painted pixels keep their index, so existing colors must stay in place
+        if palette.len() < current.len() || palette[..current.len()] != current[..] {
+            return Err(ContractError::InvalidPalette(
+                "Colors can only be appended to the palette".to_string(),
+            ));
+        }
+        PALETTE.save(deps.storage, &palette)?;
+
+        let event = Event::new("update_palette")
+            .add_attribute("sender", info.sender)
+            .add_attribute("size", palette.len().to_string());
+        Ok(Response::new().add_event(event))
+    }
+
+    pub fn update_pause_status(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        status: PauseStatus,
+    ) -> Result<Response, ContractError> {
+        assert_minter_owner(deps.storage, &info.sender)?;
+        PAUSE_STATUS.save(deps.storage, &status)?;
+
+        let event = Event::new("update_pause_status")
+            .add_attribute("sender", info.sender)
+            .add_attribute("painting", status.painting.to_string())
+            .add_attribute("minting", status.minting.to_string())
+            .add_attribute("transfers", status.transfers.to_string());
+        Ok(Response::new().add_event(event))
+    }
+
+    pub fn update_rate_limit(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        rate_limit: Option<RateLimit>,
+    ) -> Result<Response, ContractError> {
+        assert_minter_owner(deps.storage, &info.sender)?;
+
+        let event = Event::new("update_rate_limit").add_attribute("sender", info.sender);
+        let event = match rate_limit {
+            Some(rate_limit) => {
+                validate_rate_limit(&rate_limit)?;
+                RATE_LIMIT.save(deps.storage, &rate_limit)?;
+                event
+                    .add_attribute("max_updates", rate_limit.max_updates.to_string())
+                    .add_attribute("window", rate_limit.window.to_string())
+                    .add_attribute("pixel_cooldown", rate_limit.pixel_cooldown.to_string())
+            }
+            None => {
+                RATE_LIMIT.remove(deps.storage);
+                event.add_attribute("max_updates", "none")
+            }
+        };
+        Ok(Response::new().add_event(event))
+    }
+
+    pub fn grant_coloring_rights(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        grantee: String,
+        expires: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
+        // Holders and their operators grant rights, as with cw721 approvals
+        let token = self.tile_token(deps.storage, &token_id)?;
+        self.parent
+            .check_can_approve(deps.as_ref(), &env, &info, &token)
+            .map_err(|_| ContractError::Unauthorized {})?;
+
+        // Grants that are already expired would never apply
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+        let grantee = deps.api.addr_validate(&grantee)?;
+        let right = ColoringRight {
+            granter: token.owner,
+            expires,
+        };
+        COLORING_RIGHTS.save(deps.storage, (&token_id, &grantee), &right)?;
+
+        let event = Event::new("grant_coloring_rights")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("grantee", grantee)
+            .add_attribute("expires", expires.to_string());
+        Ok(Response::new().add_event(event))
+    }
+
+    pub fn revoke_coloring_rights(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        grantee: String,
+    ) -> Result<Response, ContractError> {
+        let token = self.tile_token(deps.storage, &token_id)?;
+        self.parent
+            .check_can_approve(deps.as_ref(), &env, &info, &token)
+            .map_err(|_| ContractError::Unauthorized {})?;
+
+        let grantee = deps.api.addr_validate(&grantee)?;
+        COLORING_RIGHTS.remove(deps.storage, (&token_id, &grantee));
+
+        let event = Event::new("revoke_coloring_rights")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("grantee", grantee);
+        Ok(Response::new().add_event(event))
+    }
+
+    pub fn update_protected_pixels(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+        mask: Binary,
+    ) -> Result<Response, ContractError> {
+        if self.tile_owner(deps.storage, &token_id)? != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if mask.is_empty() {
+            PROTECTED_PIXELS.remove(deps.storage, &token_id);
+        } else {
+            let canvas = CANVAS.load(deps.storage)?;
+            let expected = (canvas.tile_size as usize * canvas.tile_size as usize).div_ceil(8);
+            if mask.len() != expected {
+                return Err(ContractError::InvalidPixelUpdate(format!(
+                    "Protection mask must be {expected} bytes"
+                )));
+            }
+            PROTECTED_PIXELS.save(deps.storage, &token_id, &mask)?;
+        }
+
+        let protected: u32 = mask.iter().map(|byte| byte.count_ones()).sum();
+        let event = Event::new("update_protected_pixels")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("protected", protected.to_string());
+        Ok(Response::new().add_event(event))
+    }
+
+    pub fn set_base_image(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+        colors: Binary,
+    ) -> Result<Response, ContractError> {
+        if self.tile_owner(deps.storage, &token_id)? != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if colors.is_empty() {
+            BASE_IMAGES.remove(deps.storage, &token_id);
+        } else {
+            let canvas = CANVAS.load(deps.storage)?;
+            let expected = canvas.tile_size as usize * canvas.tile_size as usize * 3;
+            if colors.len() != expected {
+                return Err(ContractError::InvalidPixelUpdate(format!(
+                    "Base image must be {expected} bytes"
+                )));
+            }
+            let palette = PALETTE.may_load(deps.storage)?;
+            for rgb in colors.chunks_exact(3) {
+                resolve_color(palette.as_deref(), PixelColor::Rgb([rgb[0], rgb[1], rgb[2]]))?;
+            }
+            BASE_IMAGES.save(deps.storage, &token_id, &colors)?;
+        }
+
+        let event = Event::new("set_base_image")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("removed", colors.is_empty().to_string());
+        Ok(Response::new().add_event(event))
+    }
+
+    pub fn update_expiry_policy(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        policy: ExpiryPolicy,
+    ) -> Result<Response, ContractError> {
+        assert_minter_owner(deps.storage, &info.sender)?;
+        EXPIRY_POLICY.save(deps.storage, &policy)?;
+
+        let event = Event::new("update_expiry_policy")
+            .add_attribute("sender", info.sender)
+            .add_attribute("policy", format!("{:?}", policy));
+        Ok(Response::new().add_event(event))
+    }
+
+    /// Validates a pixel update and applies it to the tile it belongs to.
+    /// Tiles are loaded from storage the first time they are touched and
+    /// returned through `tiles`, along with the holder of their NFT, so
+    /// callers can persist them once. Pixels painted by anyone but the tile
+    /// owner are rented, and the rented pixel-seconds are added to `rents`
+    /// under the tile owner.
+    #[allow(clippy::too_many_arguments)]
+    fn paint_pixel(
+        &self,
+        storage: &dyn Storage,
+        env: &Env,
+        sender: &Addr,
+        canvas: &Canvas,
+        tiles: &mut BTreeMap<String, (Addr, Tile)>,
+        rents: &mut BTreeMap<Addr, u128>,
+        update: &PixelUpdate,
+        pixel_cooldown: u64,
+    ) -> Result<String, ContractError> {
+        // Map canvas coordinates to a tile
+        let position = canvas
+            .position(update.x, update.y)
+            .ok_or(ContractError::PixelOutOfRange {})?;
+
+        // Validate expiration
+        if update.expiration <= env.block.time.seconds() {
+            return Err(ContractError::InvalidExpiration {});
+        }
+
+        let token_id = position.token_id();
+
+        // Load tile, only minted tiles can be painted
+        let (owner, tile) = match tiles.entry(token_id.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let owner = self.tile_owner(storage, &token_id)?;
+                let tile = TILES
+                    .may_load(storage, token_id.clone())?
+                    .unwrap_or_else(|| blank_tile(canvas, position.tile_id));
+                entry.insert((owner, tile))
+            }
+        };
+
+        // Grantees paint as if they owned the tile
+        let is_owner = *owner == *sender
+            || COLORING_RIGHTS
+                .may_load(storage, (&token_id, sender))?
+                .is_some_and(|right| right.is_valid(owner, &env.block));
+
+        // Check pixel availability
+        let pixel_index = position.offset as usize;
+        let pixel = &tile.pixels[pixel_index];
+        if !pixel.is_available(env.block.time.seconds()) {
+            return Err(match &pixel.renter {
+                Some(renter) => ContractError::PixelLocked {
+                    renter: renter.clone(),
+                    expiration: pixel.expiration,
+                },
+                None if !is_owner => ContractError::Unauthorized {},
+                None => ContractError::InvalidPixelUpdate("Pixel is not available".to_string()),
+            });
+        }
+        if let Some(until) = cooldown_until(pixel, pixel_cooldown, env.block.time.seconds()) {
+            return Err(ContractError::PixelCooldown { until });
+        }
+
+        // Tile owners paint for free, anyone else rents the pixel unless it is protected
+        let renter = if is_owner {
+            None
+        } else {
+            let protected = PROTECTED_PIXELS
+                .may_load(storage, &token_id)?
+                .and_then(|mask| mask.get(pixel_index / 8).copied())
+                .is_some_and(|byte| byte & (1 << (pixel_index % 8)) != 0);
+            if protected {
+                return Err(ContractError::PixelProtected {
+                    x: update.x,
+                    y: update.y,
+                });
+            }
+
+            let duration = (update.expiration - env.block.time.seconds()) as u128;
+            let pixel_seconds = rents.entry(owner.clone()).or_default();
+            *pixel_seconds = pixel_seconds
+                .checked_add(duration)
+                .ok_or_else(|| StdError::generic_err("Rental duration overflow"))?;
+            Some(sender.to_string())
+        };
+
+        // Update pixel, chaining the new version to the previous one
+        tile.pixels[pixel_index].paint(update.color, update.expiration, renter, env.block.time.seconds())?;
+
+        Ok(token_id)
+    }
+
+    /// NFT of a tile, which must be minted before its pixels can be managed
+    fn tile_token(&self, storage: &dyn Storage, token_id: &str) -> Result<TokenInfo<T>, ContractError> {
+        self.parent
+            .tokens
+            .may_load(storage, token_id)?
+            .ok_or_else(|| ContractError::TileNotMinted {
+                token_id: token_id.to_string(),
+            })
+    }
+
+    /// Holder of the tile NFT, which is who owns the tile's pixels
+    fn tile_owner(&self, storage: &dyn Storage, token_id: &str) -> Result<Addr, ContractError> {
+        Ok(self.tile_token(storage, token_id)?.owner)
+    }
+
+    pub fn query_pixel(&self, deps: Deps, env: Env, x: u32, y: u32) -> StdResult<PixelResponse> {
+        let canvas = CANVAS.load(deps.storage)?;
+        let position = canvas
+            .position(x, y)
+            .ok_or_else(|| StdError::generic_err(ContractError::PixelOutOfRange {}.to_string()))?;
+
+        let pixel = TILES
+            .may_load(deps.storage, position.token_id())?
+            .map(|tile| tile.pixels[position.offset as usize].clone())
+            .unwrap_or_default();
+        let base_image = BASE_IMAGES.may_load(deps.storage, &position.token_id())?;
+
+        let view = PixelView::load(deps.storage, &env)?;
+        Ok(pixel_response(x, y, &pixel, &view, base_color(base_image.as_ref(), position.offset)))
+    }
+
+    pub fn query_tile(&self, deps: Deps, env: Env, token_id: String) -> StdResult<TileResponse> {
+        let canvas = CANVAS.load(deps.storage)?;
+        let owner = self.parent.tokens.load(deps.storage, &token_id)?.owner;
+        // Minted tiles nobody painted yet have default pixels
+        let tile = match TILES.may_load(deps.storage, token_id.clone())? {
+            Some(tile) => tile,
+            None => {
+                let tile_id = token_id
+                    .parse()
+                    .map_err(|_| StdError::generic_err(format!("Invalid token ID {token_id}")))?;
+                blank_tile(&canvas, tile_id)
+            }
+        };
+        let base_image = BASE_IMAGES.may_load(deps.storage, &token_id)?;
+        let view = PixelView::load(deps.storage, &env)?;
+        tile_response(&canvas, token_id, owner, tile, &view, base_image)
+    }
+
+    pub fn query_tiles(
+        &self,
+        deps: Deps,
+        env: Env,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TilesResponse> {
+        let canvas = CANVAS.load(deps.storage)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let view = PixelView::load(deps.storage, &env)?;
+
+        let tiles = TILES
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (token_id, tile) = item?;
+                let owner = self.parent.tokens.load(deps.storage, &token_id)?.owner;
+                let base_image = BASE_IMAGES.may_load(deps.storage, &token_id)?;
+                tile_response(&canvas, token_id, owner, tile, &view, base_image)
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TilesResponse { tiles })
+    }
+
+    pub fn query_coloring_rights(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        include_expired: Option<bool>,
+    ) -> StdResult<ColoringRightsResponse> {
+        let include_expired = include_expired.unwrap_or(false);
+        // Grants made by previous holders are void and never listed
+        let Some(token) = self.parent.tokens.may_load(deps.storage, &token_id)? else {
+            return Ok(ColoringRightsResponse { grants: vec![] });
+        };
+        let grants = COLORING_RIGHTS
+            .prefix(&token_id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter(|item| match item {
+                Ok((_, right)) => {
+                    right.granter == token.owner
+                        && (include_expired || !right.expires.is_expired(&env.block))
+                }
+                Err(_) => true,
+            })
+            .map(|item| {
+                let (grantee, right) = item?;
+                Ok(ColoringGrant {
+                    grantee: grantee.to_string(),
+                    expires: right.expires,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ColoringRightsResponse { grants })
+    }
+
     pub fn update_collection_info(
         &self,
         deps: DepsMut,
@@ -422,6 +1163,203 @@ where
     }
 }
 
+/// Rejects messages whose action the owner has paused
+fn assert_not_paused<T>(
+    storage: &dyn Storage,
+    msg: &sg721::ExecuteMsg<T, CustomExecuteMsg>,
+) -> Result<(), ContractError> {
+    let status = PAUSE_STATUS.may_load(storage)?.unwrap_or_default();
+    let action = match msg {
+        sg721::ExecuteMsg::Extension {
+            msg:
+                CustomExecuteMsg::SetPixelColor { .. }
+                | CustomExecuteMsg::SetPixelColors { .. }
+                | CustomExecuteMsg::FillRect { .. }
+                | CustomExecuteMsg::PaintRuns { .. },
+        } if status.painting => "painting",
+        sg721::ExecuteMsg::Mint { .. } if status.minting => "minting",
+        sg721::ExecuteMsg::TransferNft { .. } | sg721::ExecuteMsg::SendNft { .. }
+            if status.transfers =>
+        {
+            "transfers"
+        }
+        _ => return Ok(()),
+    };
+    Err(ContractError::Paused {
+        action: action.to_string(),
+    })
+}
+
+/// Seconds a pixel cannot be repainted after an update
+fn pixel_cooldown(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(RATE_LIMIT
+        .may_load(storage)?
+        .map(|rate_limit| rate_limit.pixel_cooldown)
+        .unwrap_or_default())
+}
+
+/// Builds the `pixel_update` event of an applied update, with its canvas coordinates
+fn pixel_event(
+    canvas: &Canvas,
+    tiles: &BTreeMap<String, (Addr, Tile)>,
+    update: &PixelUpdate,
+) -> Result<Event, ContractError> {
+    let metadata = canvas
+        .position(update.x, update.y)
+        .and_then(|position| tiles.get(&position.token_id())?.1.pixels.get(position.offset as usize))
+        .and_then(Pixel::metadata)
+        .ok_or_else(|| StdError::generic_err("Painted pixel not found"))?;
+    let event = pixel_update_event(PixelId::Canvas(canvas.pixel_id(update.x, update.y)), &metadata)?;
+    Ok(event
+        .add_attribute("x", update.x.to_string())
+        .add_attribute("y", update.y.to_string()))
+}
+
+/// Checks that the sender paid exactly the rent owed for the rented
+/// pixel-seconds in `rents` and builds the transfers to each tile owner.
+/// Rent is rounded up per owner so that short rentals are never free.
+fn collect_rent(
+    storage: &dyn Storage,
+    info: &MessageInfo,
+    rents: BTreeMap<Addr, u128>,
+) -> Result<(Uint128, Vec<BankMsg>), ContractError> {
+    let price = RENTAL_PRICE.load(storage)?;
+
+    let mut total = Uint128::zero();
+    let mut messages = vec![];
+    for (owner, pixel_seconds) in rents {
+        let amount = price
+            .amount
+            .u128()
+            .checked_mul(pixel_seconds)
+            .ok_or_else(|| StdError::generic_err("Rent overflow"))?
+            .div_ceil(SECONDS_PER_DAY as u128);
+        if amount == 0 {
+            continue;
+        }
+        total = total.checked_add(Uint128::from(amount)).map_err(StdError::from)?;
+        messages.push(BankMsg::Send {
+            to_address: owner.to_string(),
+            amount: coins(amount, &price.denom),
+        });
+    }
+
+    // Validate payment
+    if total.is_zero() {
+        nonpayable(info)?;
+    } else {
+        let expected = Coin {
+            denom: price.denom,
+            amount: total,
+        };
+        if info.funds != [expected.clone()] {
+            return Err(ContractError::InvalidFee {
+                expected,
+                received: info.funds.first().cloned(),
+            });
+        }
+    }
+
+    Ok((total, messages))
+}
+
+/// Block time and settings that pixel responses are computed with
+struct PixelView {
+    now: Timestamp,
+    pixel_cooldown: u64,
+    expiry_policy: ExpiryPolicy,
+}
+
+impl PixelView {
+    fn load(storage: &dyn Storage, env: &Env) -> StdResult<Self> {
+        Ok(PixelView {
+            now: env.block.time,
+            pixel_cooldown: pixel_cooldown(storage)?,
+            expiry_policy: EXPIRY_POLICY.may_load(storage)?.unwrap_or_default(),
+        })
+    }
+
+    /// Color shown by a pixel, applying the expiry policy once its rental expired
+    fn color(&self, pixel: &Pixel, base_color: Option<[u8; 3]>) -> [u8; 3] {
+        if pixel.renter.is_none() || !pixel.is_available(self.now.seconds()) {
+            return pixel.color;
+        }
+        match self.expiry_policy {
+            ExpiryPolicy::KeepLastColor => pixel.color,
+            ExpiryPolicy::RevertToBase => base_color.unwrap_or_default(),
+            ExpiryPolicy::RevertToBlank => Pixel::default().color,
+        }
+    }
+}
+
+/// Color of the base image at a pixel offset
+fn base_color(base_image: Option<&Binary>, offset: u32) -> Option<[u8; 3]> {
+    let start = offset as usize * 3;
+    let rgb = base_image?.get(start..start + 3)?;
+    Some([rgb[0], rgb[1], rgb[2]])
+}
+
+fn pixel_response(
+    x: u32,
+    y: u32,
+    pixel: &Pixel,
+    view: &PixelView,
+    base_color: Option<[u8; 3]>,
+) -> PixelResponse {
+    let now = view.now.seconds();
+    PixelResponse {
+        x,
+        y,
+        color: view.color(pixel, base_color),
+        expiration: pixel.expiration,
+        is_available: pixel.is_available(now),
+        renter: pixel.renter.clone(),
+        version: pixel.version,
+        previous_hash: pixel.previous_hash.clone(),
+        cooldown_until: cooldown_until(pixel, view.pixel_cooldown, now),
+    }
+}
+
+/// Tile of default pixels, as it is before any of them is painted
+fn blank_tile(canvas: &Canvas, tile_id: u32) -> Tile {
+    Tile {
+        tile_id,
+        pixels: vec![Pixel::default(); canvas.tile_size as usize * canvas.tile_size as usize],
+    }
+}
+
+fn tile_response(
+    canvas: &Canvas,
+    token_id: String,
+    owner: Addr,
+    tile: Tile,
+    view: &PixelView,
+    base_image: Option<Binary>,
+) -> StdResult<TileResponse> {
+    let pixels = tile
+        .pixels
+        .iter()
+        .enumerate()
+        .map(|(offset, pixel)| {
+            let position = PixelPosition {
+                tile_id: tile.tile_id,
+                offset: offset as u32,
+            };
+            let (x, y) = canvas
+                .coordinates(position)
+                .ok_or_else(|| StdError::generic_err(ContractError::PixelOutOfRange {}.to_string()))?;
+            let base_color = base_color(base_image.as_ref(), position.offset);
+            Ok(pixel_response(x, y, pixel, view, base_color))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TileResponse {
+        token_id,
+        owner: owner.to_string(),
+        pixels,
+    })
+}
+
 pub fn share_validate(share: Decimal) -> Result<Decimal, ContractError> {
     if share > Decimal::one() {
         return Err(ContractError::InvalidRoyalties(
@@ -1,5 +1,5 @@
 use cosmwasm_std::{StdError, Coin};
-use cw_utils::PaymentError;
+use cw_utils::{Duration, PaymentError};
 use thiserror::Error;
 use url::ParseError;
 
@@ -26,9 +26,45 @@ pub enum ContractError {
     #[error("Pixel is out of range")]
     PixelOutOfRange {},
 
+    #[error("Invalid canvas: {0}")]
+    InvalidCanvas(String),
+
+    #[error("Pixel is locked by renter {renter} until {expiration}")]
+    PixelLocked { renter: String, expiration: u64 },
+
+    #[error("Tile {token_id} has not been minted")]
+    TileNotMinted { token_id: String },
+
+    #[error("Pixel ({x}, {y}) is protected by the tile owner")]
+    PixelProtected { x: u32, y: u32 },
+
     #[error("Invalid pixel update: {0}")]
     InvalidPixelUpdate(String),
 
+    #[error("Contract is paused for {action}")]
+    Paused { action: String },
+
+    #[error("Invalid rate limit: {0}")]
+    InvalidRateLimit(String),
+
+    #[error("Rate limit of {max_updates} pixel updates per {window} exceeded")]
+    RateLimitExceeded { max_updates: u32, window: Duration },
+
+    #[error("Pixel is cooling down until {until}")]
+    PixelCooldown { until: u64 },
+
+    #[error("Invalid palette: {0}")]
+    InvalidPalette(String),
+
+    #[error("Palette mode is disabled")]
+    PaletteDisabled {},
+
+    #[error("Color {color:?} is not in the palette")]
+    ColorNotInPalette { color: [u8; 3] },
+
+    #[error("Palette index {index} is out of range")]
+    InvalidPaletteIndex { index: u8 },
+
     #[error("Invalid fee. Expected {expected:?}, received {received:?}")]
     InvalidFee {
         expected: Coin,
@@ -66,6 +102,16 @@ pub enum ContractError {
     FeatureDisabled { feature: String },
 }
 
+impl From<mosaic_types::Error> for ContractError {
+    fn from(err: mosaic_types::Error) -> Self {
+        match err {
+            mosaic_types::Error::InvalidCanvas(reason) => ContractError::InvalidCanvas(reason.to_string()),
+            mosaic_types::Error::PixelOutOfRange => ContractError::PixelOutOfRange {},
+            err => ContractError::Std(StdError::generic_err(err.to_string())),
+        }
+    }
+}
+
 impl From<cw721_base::ContractError> for ContractError {
     fn from(err: cw721_base::ContractError) -> Self {
         ContractError::Base(err.to_string())
@@ -1,11 +1,15 @@
+pub mod canvas;
 pub mod contract;
 mod error;
+pub mod events;
+mod migrations;
 pub mod msg;
+pub mod palette;
+mod rate_limit;
 mod state;
 
 pub use crate::error::ContractError;
 pub use crate::state::Sg721Contract;
-use cosmwasm_schema::cw_serde;
 use cosmwasm_std::Empty;
 use cw721_base::Extension;
 use msg::CustomExecuteMsg;
@@ -15,16 +19,18 @@ pub type QueryMsg = cw721_base::QueryMsg<Empty>;
 
 pub mod entry {
     use super::*;
-    use crate::{msg::QueryMsg, state::Sg721Contract};
+    use crate::{
+        msg::{InstantiateMsg, MigrateMsg, QueryMsg},
+        state::Sg721Contract,
+    };
 
     #[cfg(not(feature = "library"))]
     use cosmwasm_std::entry_point;
     use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
     use cw2::set_contract_version;
-    use sg721::InstantiateMsg;
 
     // version info for migration info
-    pub const CONTRACT_NAME: &str = "crates.io:mosaic-nft";
+    pub const CONTRACT_NAME: &str = "crates.io:mosaic-canvas";
     pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
     #[cfg_attr(not(feature = "library"), entry_point)]
@@ -58,17 +64,11 @@ pub mod entry {
     pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         Sg721Contract::<Extension>::default().query(deps, env, msg)
     }
-}
-
-#[cw_serde]
-pub struct TileExtension {
-    pub pixels: Vec<Pixel>,
-}
 
-#[cw_serde]
-pub struct Pixel {
-    pub color: [u8; 3],
-    pub expiration: u64,
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+        crate::migrations::migrate(deps, env, msg)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,139 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, DepsMut, Env, Order, Response, StdResult, Uint128};
+use cw2::{get_contract_version, set_contract_version};
+use cw721_base::state::TokenInfo;
+use cw721_base::Extension;
+use cw_storage_plus::Map;
+use semver::Version;
+
+use crate::canvas::Canvas;
+use crate::contract::{DEFAULT_RENTAL_DENOM, DEFAULT_RENTAL_PRICE};
+use crate::entry::{CONTRACT_NAME, CONTRACT_VERSION};
+use crate::msg::MigrateMsg;
+use crate::state::{Pixel, Sg721Contract, Tile, CANVAS, RENTAL_PRICE, TILES};
+use crate::ContractError;
+
+/// cw2 name and version of the contracts released before they had names of their own
+const LEGACY_CONTRACT_NAME: &str = "crates.io:mosaic-nft";
+const LEGACY_CONTRACT_VERSION: &str = "0.1.0";
+
+/// A state migration, run when upgrading from a version older than the one it is registered for
+type MigrationStep = fn(DepsMut, &MigrateMsg) -> Result<(), ContractError>;
+
+/// Migration steps in ascending version order
+const MIGRATIONS: &[(&str, MigrationStep)] = &[("0.2.0", v0_2_0::migrate)];
+
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    // Both contracts stored the same name up to 0.1.0
+    let legacy =
+        stored.contract == LEGACY_CONTRACT_NAME && stored.version == LEGACY_CONTRACT_VERSION;
+    if stored.contract != CONTRACT_NAME && !legacy {
+        return Err(ContractError::MigrationError(format!(
+            "cannot migrate from contract {}",
+            stored.contract
+        )));
+    }
+
+    let from = parse_version(&stored.version)?;
+    let to = parse_version(CONTRACT_VERSION)?;
+    if from > to {
+        return Err(ContractError::MigrationError(format!(
+            "cannot downgrade from {from} to {to}"
+        )));
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", from.to_string())
+        .add_attribute("to_version", to.to_string());
+
+    for (version, step) in MIGRATIONS {
+        let version = parse_version(version)?;
+        if from < version && version <= to {
+            step(deps.branch(), &msg)?;
+            res = res.add_attribute("migration", version.to_string());
+        }
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(res)
+}
+
+fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version)
+        .map_err(|err| ContractError::MigrationError(format!("invalid version {version}: {err}")))
+}
+
+/// 0.1.0 stored tiles as JSON with bare `{color, expiration}` pixels and the
+/// first painter as owner, and had no canvas or rental price. Tiles are packed
+/// into the layout of `Tile::to_packed`, leaving ownership to the tile NFTs,
+/// which are minted to the first painter where they do not exist yet.
+mod v0_2_0 {
+    use super::*;
+
+    #[cw_serde]
+    pub struct LegacyPixel {
+        pub color: [u8; 3],
+        pub expiration: u64,
+    }
+
+    #[cw_serde]
+    pub struct LegacyTile {
+        pub owner: Addr,
+        pub pixels: Vec<LegacyPixel>,
+    }
+
+    pub const LEGACY_TILES: Map<String, LegacyTile> = Map::new("tiles");
+
+    pub fn migrate(deps: DepsMut, _msg: &MigrateMsg) -> Result<(), ContractError> {
+        let storage = deps.storage;
+        let contract = Sg721Contract::<Extension>::default();
+
+        // Re-encode every tile with unversioned pixels
+        let tiles = LEGACY_TILES
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (token_id, tile) in tiles {
+            let tile_id = token_id.parse().map_err(|_| {
+                ContractError::MigrationError(format!("invalid tile token ID {token_id}"))
+            })?;
+            if !contract.parent.tokens.has(storage, &token_id) {
+                let token = TokenInfo {
+                    owner: tile.owner,
+                    approvals: vec![],
+                    token_uri: None,
+                    extension: None,
+                };
+                contract.parent.tokens.save(storage, &token_id, &token)?;
+                contract.parent.increment_tokens(storage)?;
+            }
+            let pixels = tile
+                .pixels
+                .into_iter()
+                .map(|pixel| Pixel {
+                    color: pixel.color,
+                    expiration: pixel.expiration,
+                    ..Pixel::default()
+                })
+                .collect();
+            TILES.save(storage, token_id, &Tile { tile_id, pixels })?;
+        }
+
+        if CANVAS.may_load(storage)?.is_none() {
+            CANVAS.save(storage, &Canvas::default())?;
+        }
+        if RENTAL_PRICE.may_load(storage)?.is_none() {
+            RENTAL_PRICE.save(
+                storage,
+                &Coin {
+                    denom: DEFAULT_RENTAL_DENOM.to_string(),
+                    amount: Uint128::from(DEFAULT_RENTAL_PRICE),
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+}
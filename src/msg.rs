@@ -1,22 +1,151 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Binary, Empty, Timestamp};
+use cosmwasm_std::{Binary, Coin, Empty, Timestamp};
 use cw721::{
     AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, NftInfoResponse,
     NumTokensResponse, OperatorsResponse, OwnerOfResponse, TokensResponse,
 };
 use cw721_base::{msg::MinterResponse, Extension};
 use cw_ownable::{cw_ownable_execute, cw_ownable_query};
-use cw_utils::Expiration;
-use sg721::{RoyaltyInfoResponse, UpdateCollectionInfoMsg};
+use cw_utils::{Duration, Expiration};
+use sg721::{CollectionInfo, RoyaltyInfoResponse, UpdateCollectionInfoMsg};
+
+use crate::canvas::Canvas;
+
+/// sg721 instantiation with the optional color palette of the collection
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub minter: String,
+    pub collection_info: CollectionInfo<RoyaltyInfoResponse>,
+    /// Up to 256 colors pixels are restricted to, `None` allows any RGB color
+    pub palette: Option<Vec<[u8; 3]>>,
+    /// Limits on how fast pixels can be painted, `None` for no limit
+    pub rate_limit: Option<RateLimit>,
+}
+
+/// Throttling of paint messages
+#[cw_serde]
+pub struct RateLimit {
+    /// Pixel updates a single address can make within `window`
+    pub max_updates: u32,
+    /// Sliding window in blocks or seconds
+    pub window: Duration,
+    /// Seconds after an update during which the pixel cannot be repainted, `0` for none
+    pub pixel_cooldown: u64,
+}
+
+/// Actions halted by the owner, e.g. during an incident
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseStatus {
+    /// Halts every paint message
+    pub painting: bool,
+    /// Halts minting tiles
+    pub minting: bool,
+    /// Halts transferring and sending tiles
+    pub transfers: bool,
+}
+
+/// What a rented pixel shows once its rental has expired
+#[cw_serde]
+#[derive(Default)]
+pub enum ExpiryPolicy {
+    /// Keep the color of the renter until the pixel is repainted
+    #[default]
+    KeepLastColor,
+    /// Show the base image of the tile owner, blank where there is none
+    RevertToBase,
+    /// Show a blank pixel
+    RevertToBlank,
+}
+
+/// A color given either as RGB or as an index into the palette
+#[cw_serde]
+#[serde(untagged)]
+pub enum PixelColor {
+    Rgb([u8; 3]),
+    Index(u8),
+}
 
 #[cw_serde]
 pub enum CustomExecuteMsg {
     SetPixelColor {
-        pixel_id: u32,
+        x: u32,
+        y: u32,
         current_tile_metadata: Binary,
+        color: PixelColor,
+        expiration: u64,
+    },
+    SetPixelColors {
+        updates: Vec<PixelUpdate>,
+    },
+    /// Paint every pixel of a rectangle with a single color
+    FillRect {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
         color: [u8; 3],
         expiration: u64,
     },
+    /// Paint a run-length encoded image of the given width, starting at (x, y)
+    PaintRuns {
+        x: u32,
+        y: u32,
+        width: u32,
+        runs: Vec<PaintRun>,
+        expiration: u64,
+    },
+    UpdateCanvas {
+        canvas: Canvas,
+    },
+    /// Set the price of renting a single pixel for one day, only callable by the owner
+    UpdateRentalPrice {
+        price: Coin,
+    },
+    /// Extend the palette, only callable by the owner until the collection info is frozen.
+    /// The new palette must start with the current one, as painted pixels keep their index.
+    UpdatePalette {
+        palette: Vec<[u8; 3]>,
+    },
+    /// Set or remove the paint rate limit, only callable by the owner
+    UpdateRateLimit {
+        rate_limit: Option<RateLimit>,
+    },
+    /// Let `grantee` paint the tile free of rent until `expires`, only callable by the tile owner
+    GrantColoringRights {
+        token_id: String,
+        grantee: String,
+        expires: Option<Expiration>,
+    },
+    /// Remove previously granted coloring rights, only callable by the tile owner
+    RevokeColoringRights {
+        token_id: String,
+        grantee: String,
+    },
+    /// Set the pixels of a tile that cannot be rented as a bitmask with one bit per
+    /// pixel offset, lowest bit first. An empty mask protects nothing.
+    /// Only callable by the tile owner.
+    UpdateProtectedPixels {
+        token_id: String,
+        mask: Binary,
+    },
+    /// Set the base image of a tile as 3 RGB bytes per pixel offset, shown by expired
+    /// rentals under the `RevertToBase` policy. An empty image removes it.
+    /// Only callable by the tile owner.
+    SetBaseImage {
+        token_id: String,
+        colors: Binary,
+    },
+    /// Set what expired rentals show, only callable by the owner
+    UpdateExpiryPolicy {
+        policy: ExpiryPolicy,
+    },
+    /// Pause or resume painting, minting and transfers, only callable by the owner
+    UpdatePauseStatus {
+        status: PauseStatus,
+    },
     UpdateCollectionInfo {
         collection_info: UpdateCollectionInfoMsg<RoyaltyInfoResponse>,
     },
@@ -35,17 +164,98 @@ pub struct CollectionInfoResponse {
     pub royalty_info: Option<RoyaltyInfoResponse>,
 }
 
+/// Pixel state as stored on chain
+#[cw_serde]
+pub struct PixelResponse {
+    /// Horizontal position of the pixel on the canvas
+    pub x: u32,
+    /// Vertical position of the pixel on the canvas
+    pub y: u32,
+    /// The RGB color shown by the pixel, following the expiry policy once a rental expired
+    pub color: [u8; 3],
+    /// The expiration timestamp of the current color
+    pub expiration: u64,
+    /// Whether the pixel can be painted at the current block time
+    pub is_available: bool,
+    /// Address that rented the pixel, `None` when painted by the tile owner
+    pub renter: Option<String>,
+    /// Number of times the pixel has been painted
+    pub version: u32,
+    /// Metadata hash of the previous version of the pixel
+    pub previous_hash: Option<String>,
+    /// Time in seconds until which the pixel cooldown blocks repainting
+    pub cooldown_until: Option<u64>,
+}
+
+/// Tile state as stored on chain
+#[cw_serde]
+pub struct TileResponse {
+    pub token_id: String,
+    /// Holder of the tile NFT
+    pub owner: String,
+    pub pixels: Vec<PixelResponse>,
+}
+
+#[cw_serde]
+pub struct TilesResponse {
+    pub tiles: Vec<TileResponse>,
+}
+
+/// An address allowed to paint a tile as its owner
+#[cw_serde]
+pub struct ColoringGrant {
+    pub grantee: String,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct ProtectedPixelsResponse {
+    /// One bit per pixel offset, lowest bit first, empty when nothing is protected
+    pub mask: Binary,
+}
+
+#[cw_serde]
+pub struct BaseImageResponse {
+    /// 3 RGB bytes per pixel offset, empty when the tile has no base image
+    pub colors: Binary,
+}
+
+#[cw_serde]
+pub struct ColoringRightsResponse {
+    pub grants: Vec<ColoringGrant>,
+}
+
+/// Pixel updates an address can currently make under the rate limit
+#[cw_serde]
+pub struct PaintAllowanceResponse {
+    /// Updates left in the current window, `None` without a rate limit
+    pub remaining: Option<u32>,
+    /// Blocks or seconds until another update is allowed, `None` when one is allowed now
+    pub available_in: Option<Duration>,
+}
+
 /// Pixel update information
 #[cw_serde]
 pub struct PixelUpdate {
-    /// The ID of the pixel to update
-    pub pixel_id: u32,
+    /// Horizontal position of the pixel on the canvas
+    pub x: u32,
+    /// Vertical position of the pixel on the canvas
+    pub y: u32,
     /// The new RGB color for the pixel
     pub color: [u8; 3],
     /// The expiration timestamp for this update
     pub expiration: u64,
 }
 
+/// A run of consecutive pixels sharing the same color
+#[cw_serde]
+pub struct PaintRun {
+    /// Number of pixels in the run
+    pub length: u32,
+    /// The RGB color of the run, or `None` to leave the pixels untouched
+    pub color: Option<[u8; 3]>,
+}
+
 #[cw_ownable_execute]
 #[cw_serde]
 pub enum ExecuteMsg<T, E> {
@@ -147,10 +357,42 @@ pub enum QueryMsg {
     Minter {},
     #[returns(CollectionInfoResponse)]
     CollectionInfo {},
+    #[returns(Canvas)]
+    Canvas {},
+    #[returns(Coin)]
+    RentalPrice {},
+    #[returns(Option<Vec<[u8; 3]>>)]
+    Palette {},
+    #[returns(PauseStatus)]
+    PauseStatus {},
+    #[returns(Option<RateLimit>)]
+    RateLimit {},
+    #[returns(PaintAllowanceResponse)]
+    PaintAllowance { address: String },
+    #[returns(PixelResponse)]
+    Pixel { x: u32, y: u32 },
+    #[returns(TileResponse)]
+    Tile { token_id: String },
+    #[returns(TilesResponse)]
+    Tiles {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(ProtectedPixelsResponse)]
+    ProtectedPixels { token_id: String },
+    #[returns(BaseImageResponse)]
+    BaseImage { token_id: String },
+    #[returns(ExpiryPolicy)]
+    ExpiryPolicy {},
+    #[returns(ColoringRightsResponse)]
+    ColoringRights {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
 }
 
-// Re-export base messages
-pub use sg721::InstantiateMsg;
+#[cw_serde]
+pub struct MigrateMsg {}
 
 // Create a type alias for our execute messages
 pub type MosaicExecuteMsg = ExecuteMsg<Extension, CustomExecuteMsg>; 
\ No newline at end of file
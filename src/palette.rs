@@ -0,0 +1,38 @@
+use crate::msg::PixelColor;
+use crate::ContractError;
+
+/// Pixels store a single byte palette index
+pub const MAX_PALETTE_SIZE: usize = 256;
+
+/// Checks that a palette is non-empty, fits a byte index and has no duplicates
+pub fn validate_palette(palette: &[[u8; 3]]) -> Result<(), ContractError> {
+    if palette.is_empty() || palette.len() > MAX_PALETTE_SIZE {
+        return Err(ContractError::InvalidPalette(format!(
+            "Palette must have between 1 and {MAX_PALETTE_SIZE} colors"
+        )));
+    }
+    for (index, color) in palette.iter().enumerate() {
+        if palette[..index].contains(color) {
+            return Err(ContractError::InvalidPalette(format!("Duplicate color {color:?}")));
+        }
+    }
+    Ok(())
+}
+
+/// Resolves a color to RGB. With a palette, RGB colors must be part of it and
+/// indexes must be in range. Without one, only RGB colors are accepted.
+pub fn resolve_color(
+    palette: Option<&[[u8; 3]]>,
+    color: PixelColor,
+) -> Result<[u8; 3], ContractError> {
+    match (palette, color) {
+        (None, PixelColor::Rgb(color)) => Ok(color),
+        (None, PixelColor::Index(_)) => Err(ContractError::PaletteDisabled {}),
+        (Some(palette), PixelColor::Rgb(color)) if palette.contains(&color) => Ok(color),
+        (Some(_), PixelColor::Rgb(color)) => Err(ContractError::ColorNotInPalette { color }),
+        (Some(palette), PixelColor::Index(index)) => palette
+            .get(index as usize)
+            .copied()
+            .ok_or(ContractError::InvalidPaletteIndex { index }),
+    }
+}
@@ -0,0 +1,115 @@
+use cosmwasm_std::{Addr, BlockInfo, Deps, Env, StdResult, Storage};
+use cw_utils::Duration;
+
+use crate::msg::{PaintAllowanceResponse, RateLimit};
+use crate::state::{PaintRecord, Pixel, PAINT_HISTORY, RATE_LIMIT};
+use crate::ContractError;
+
+pub fn validate_rate_limit(rate_limit: &RateLimit) -> Result<(), ContractError> {
+    if rate_limit.max_updates == 0 {
+        return Err(ContractError::InvalidRateLimit(
+            "Max updates must be greater than zero".to_string(),
+        ));
+    }
+    if matches!(rate_limit.window, Duration::Height(0) | Duration::Time(0)) {
+        return Err(ContractError::InvalidRateLimit("Window cannot be empty".to_string()));
+    }
+    Ok(())
+}
+
+/// Records `count` pixel updates by `sender`, failing when they exceed the
+/// rate limit. Records that left the window are pruned on the way.
+pub fn consume_rate_limit(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    count: u32,
+) -> Result<(), ContractError> {
+    let Some(rate_limit) = RATE_LIMIT.may_load(storage)? else {
+        return Ok(());
+    };
+
+    let mut records = recent_records(storage, &rate_limit, &env.block, sender)?;
+    let used: u32 = records.iter().map(|record| record.count).sum();
+    if used.saturating_add(count) > rate_limit.max_updates {
+        return Err(ContractError::RateLimitExceeded {
+            max_updates: rate_limit.max_updates,
+            window: rate_limit.window,
+        });
+    }
+
+    match records.last_mut() {
+        Some(record) if record.height == env.block.height => record.count += count,
+        _ => records.push(PaintRecord {
+            height: env.block.height,
+            time: env.block.time.seconds(),
+            count,
+        }),
+    }
+    PAINT_HISTORY.save(storage, sender, &records)?;
+    Ok(())
+}
+
+/// Time in seconds until which the pixel cooldown blocks repainting
+pub fn cooldown_until(pixel: &Pixel, pixel_cooldown: u64, now: u64) -> Option<u64> {
+    let until = pixel.updated_at.saturating_add(pixel_cooldown);
+    (pixel.version > 0 && until > now).then_some(until)
+}
+
+pub fn query_paint_allowance(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<PaintAllowanceResponse> {
+    let sender = deps.api.addr_validate(&address)?;
+    let Some(rate_limit) = RATE_LIMIT.may_load(deps.storage)? else {
+        return Ok(PaintAllowanceResponse {
+            remaining: None,
+            available_in: None,
+        });
+    };
+
+    let records = recent_records(deps.storage, &rate_limit, &env.block, &sender)?;
+    let used: u32 = records.iter().map(|record| record.count).sum();
+    let remaining = rate_limit.max_updates.saturating_sub(used);
+
+    // Once full, the window frees up as its oldest records expire
+    let mut available_in = None;
+    if remaining == 0 {
+        let mut freed = 0;
+        for record in &records {
+            freed += record.count;
+            if used - freed < rate_limit.max_updates {
+                available_in = Some(match rate_limit.window {
+                    Duration::Height(window) => {
+                        Duration::Height(record.height.saturating_add(window) - env.block.height)
+                    }
+                    Duration::Time(window) => {
+                        Duration::Time(record.time.saturating_add(window) - env.block.time.seconds())
+                    }
+                });
+                break;
+            }
+        }
+    }
+
+    Ok(PaintAllowanceResponse {
+        remaining: Some(remaining),
+        available_in,
+    })
+}
+
+/// Records of `sender` that are still inside the window
+fn recent_records(
+    storage: &dyn Storage,
+    rate_limit: &RateLimit,
+    block: &BlockInfo,
+    sender: &Addr,
+) -> StdResult<Vec<PaintRecord>> {
+    let mut records = PAINT_HISTORY.may_load(storage, sender)?.unwrap_or_default();
+    records.retain(|record| match rate_limit.window {
+        Duration::Height(window) => record.height.saturating_add(window) > block.height,
+        Duration::Time(window) => record.time.saturating_add(window) > block.time.seconds(),
+    });
+    Ok(records)
+}
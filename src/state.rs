@@ -1,24 +1,133 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Empty, Timestamp};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Binary, BlockInfo, Coin, Empty, Order, StdError, StdResult, Storage, Timestamp};
+use cw_storage_plus::{Bound, Item, Map};
 use serde::{de::DeserializeOwned, Serialize};
+use cw_utils::Expiration;
 use sg721::{CollectionInfo, RoyaltyInfo};
 use std::ops::Deref;
 
+use crate::canvas::Canvas;
+use crate::msg::{ExpiryPolicy, PauseStatus, RateLimit};
+
+pub use mosaic_types::{Pixel, Tile};
+
+// Key is token_id
+pub const TILES: TileMap = TileMap::new("tiles");
+
+/// Tiles keyed by token_id, stored in the packed layout of `Tile::to_packed`
+/// rather than as JSON to keep paint reads and writes small
+pub struct TileMap<'a> {
+    namespace: &'a str,
+}
+
+impl<'a> TileMap<'a> {
+    pub const fn new(namespace: &'a str) -> Self {
+        TileMap { namespace }
+    }
+
+    // Only used for its key encoding, values never go through serde
+    fn keys(&self) -> Map<'a, String, Tile> {
+        Map::new(self.namespace)
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage, token_id: String, tile: &Tile) -> StdResult<()> {
+        let palette = PALETTE.may_load(storage)?;
+        let data = tile.to_packed(palette.as_deref()).map_err(|err| StdError::generic_err(err.to_string()))?;
+        storage.set(&self.keys().key(token_id), &data);
+        Ok(())
+    }
+
+    pub fn may_load(&self, storage: &dyn Storage, token_id: String) -> StdResult<Option<Tile>> {
+        let Some(data) = storage.get(&self.keys().key(token_id.clone())) else {
+            return Ok(None);
+        };
+        let tile_id = token_id
+            .parse()
+            .map_err(|_| StdError::parse_err("Tile", format!("invalid token ID {token_id}")))?;
+        let palette = PALETTE.may_load(storage)?;
+        Tile::from_packed(tile_id, &data, palette.as_deref())
+            .map(Some)
+            .map_err(|err| StdError::parse_err("Tile", err.to_string()))
+    }
+
+    pub fn load(&self, storage: &dyn Storage, token_id: String) -> StdResult<Tile> {
+        self.may_load(storage, token_id)?
+            .ok_or_else(|| StdError::not_found("mosaic_contract::state::Tile"))
+    }
+
+    pub fn is_empty(&self, storage: &dyn Storage) -> bool {
+        self.keys().is_empty(storage)
+    }
+
+    pub fn range<'c>(
+        &self,
+        storage: &'c dyn Storage,
+        min: Option<Bound<'a, String>>,
+        max: Option<Bound<'a, String>>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = StdResult<(String, Tile)>> + 'c>
+    where
+        'a: 'c,
+    {
+        let tiles = Self::new(self.namespace);
+        Box::new(self.keys().keys_raw(storage, min, max, order).map(move |key| {
+            let token_id = String::from_utf8(key).map_err(StdError::from)?;
+            let tile = tiles.load(storage, token_id.clone())?;
+            Ok((token_id, tile))
+        }))
+    }
+}
+
+// Canvas dimensions used to map coordinates to tiles
+pub const CANVAS: Item<Canvas> = Item::new("canvas");
+
+// Colors pixels are restricted to, absent when any RGB color is allowed
+pub const PALETTE: Item<Vec<[u8; 3]>> = Item::new("palette");
+
+// Actions paused by the owner, nothing is paused when absent
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
+
+// Paint throttling, absent when painting is not rate limited
+pub const RATE_LIMIT: Item<RateLimit> = Item::new("rate_limit");
+
+/// Pixel updates made by an address in one block
 #[cw_serde]
-pub struct Pixel {
-    pub color: [u8; 3],
-    pub expiration: u64,
+pub struct PaintRecord {
+    pub height: u64,
+    pub time: u64,
+    pub count: u32,
 }
 
+// Recent pixel updates of each address, pruned once they leave the rate limit window
+pub const PAINT_HISTORY: Map<&Addr, Vec<PaintRecord>> = Map::new("paint_history");
+
+/// Coloring rights given by a tile holder, void once the tile changes hands
 #[cw_serde]
-pub struct Tile {
-    pub owner: Addr,
-    pub pixels: Vec<Pixel>,
+pub struct ColoringRight {
+    pub granter: Addr,
+    pub expires: Expiration,
 }
 
-// Key is token_id
-pub const TILES: Map<String, Tile> = Map::new("tiles");
+impl ColoringRight {
+    pub fn is_valid(&self, owner: &Addr, block: &BlockInfo) -> bool {
+        self.granter == *owner && !self.expires.is_expired(block)
+    }
+}
+
+// Addresses allowed to paint a tile as if they owned it, keyed by token ID and grantee
+pub const COLORING_RIGHTS: Map<(&str, &Addr), ColoringRight> = Map::new("coloring_rights");
+
+// Pixels of a tile that cannot be rented, one bit per pixel offset, lowest bit first
+pub const PROTECTED_PIXELS: Map<&str, Binary> = Map::new("protected_pixels");
+
+// Colors the tile owner chose for each pixel offset, 3 RGB bytes per pixel
+pub const BASE_IMAGES: Map<&str, Binary> = Map::new("base_images");
+
+// What expired rentals show, the last color when absent
+pub const EXPIRY_POLICY: Item<ExpiryPolicy> = Item::new("expiry_policy");
+
+// Price of renting a single pixel for one day
+pub const RENTAL_PRICE: Item<Coin> = Item::new("rental_price");
 
 // Collection info storage
 pub const COLLECTION_INFO: Item<CollectionInfo<RoyaltyInfo>> = Item::new("collection_info");
@@ -1,15 +1,29 @@
 use cosmwasm_std::{
     testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage},
-    Binary, OwnedDeps, SystemResult, ContractResult, to_json_binary,
+    Binary, OwnedDeps, SystemResult, ContractResult, from_json, coins, BankMsg, Coin, Uint128,
+    Addr, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
 };
-use cw721_base::Extension;
+use cosmwasm_schema::cw_serde;
+use cw_multi_test::{App, ContractWrapper, Executor};
+use cw_storage_plus::Map;
+use cw_utils::{Duration, Expiration};
 
 use crate::{
-    entry::{execute, instantiate},
-    msg::CustomExecuteMsg,
+    canvas::{Canvas, PixelPosition, DEFAULT_CANVAS_WIDTH},
+    entry::{execute, instantiate, migrate, query, CONTRACT_NAME, CONTRACT_VERSION},
+    msg::{
+        BaseImageResponse, ColoringGrant, ColoringRightsResponse, CustomExecuteMsg, ExpiryPolicy, InstantiateMsg, MigrateMsg, PaintAllowanceResponse, PaintRun,
+        PauseStatus, PixelColor, PixelResponse, PixelUpdate, ProtectedPixelsResponse, QueryMsg, RateLimit, TileResponse,
+        TilesResponse,
+    },
     error::ContractError,
+    events::{Color, PixelMetadata, EVENT_TYPE_PIXEL_UPDATE},
+    state::TILES,
+    Sg721Contract,
 };
-use sg721::InstantiateMsg;
+use cosmwasm_std::HexBinary;
+use cw721_base::state::TokenInfo;
+use sha2::{Digest, Sha256};
 
 const MINTER: &str = "minter";
 const PIXELS_PER_TILE: u32 = 100;
@@ -24,6 +38,13 @@ fn mock_dependencies_with_querier() -> OwnedDeps<MockStorage, MockApi, MockQueri
 }
 
 fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+    setup_contract_with_palette(deps, None)
+}
+
+fn setup_contract_with_palette(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    palette: Option<Vec<[u8; 3]>>,
+) {
     let env = mock_env();
     let info = mock_info(MINTER, &[]);
 
@@ -41,11 +62,23 @@ fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) {
             explicit_content: Some(false),
             start_trading_time: None,
         },
+        palette,
+        rate_limit: None,
     };
 
     instantiate(deps.as_mut(), env, info, msg).unwrap();
 }
 
+fn mint_tile(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, token_id: &str, owner: &str) {
+    let msg = sg721::ExecuteMsg::Mint {
+        token_id: token_id.to_string(),
+        owner: owner.to_string(),
+        token_uri: None,
+        extension: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg).unwrap();
+}
+
 #[test]
 fn set_pixel_color_invalid_pixel() {
     let mut deps = mock_dependencies_with_querier();
@@ -65,6 +98,8 @@ fn set_pixel_color_invalid_pixel() {
             explicit_content: Some(false),
             start_trading_time: None,
         },
+        palette: None,
+        rate_limit: None,
     };
 
     let info = mock_info(MINTER, &[]);
@@ -73,19 +108,20 @@ fn set_pixel_color_invalid_pixel() {
     // Create a new tile
     let current_tile_metadata = Binary::from(vec![0u8; 32]); // Mock metadata
 
-    // Try to update a pixel outside the tile range
+    // Try to update a pixel outside the canvas
     let msg = sg721::ExecuteMsg::Extension {
         msg: CustomExecuteMsg::SetPixelColor {
-            pixel_id: PIXELS_PER_TILE + 1, // Invalid pixel ID
+            x: DEFAULT_CANVAS_WIDTH, // Out of range
+            y: 0,
             current_tile_metadata,
-            color: [255, 0, 0],
+            color: PixelColor::Rgb([255, 0, 0]),
             expiration: env.block.time.seconds() + 3600,
         },
     };
 
     // Execute the message
     let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    assert!(matches!(err, ContractError::InvalidPixelId {}));
+    assert!(matches!(err, ContractError::PixelOutOfRange {}));
 }
 
 #[test]
@@ -103,4 +139,1205 @@ fn burn_is_disabled() {
     // Execute the message
     let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
     assert!(matches!(err, ContractError::FeatureDisabled { feature } if feature == "burn"));
-} 
\ No newline at end of file
+} 
+#[test]
+fn query_pixel_reports_availability() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_tile(&mut deps, "0", MINTER);
+    let env = mock_env();
+    let info = mock_info(MINTER, &[]);
+
+    // Unpainted pixels are available with the default color
+    let res: PixelResponse =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x: 5, y: 0 }).unwrap())
+            .unwrap();
+    assert_eq!(res.color, [0, 0, 0]);
+    assert!(res.is_available);
+
+    let expiration = env.block.time.seconds() + 3600;
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x: 5,
+            y: 0,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color: PixelColor::Rgb([255, 0, 0]),
+            expiration,
+        },
+    };
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res: PixelResponse =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x: 5, y: 0 }).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        PixelResponse {
+            x: 5,
+            y: 0,
+            color: [255, 0, 0],
+            expiration,
+            is_available: false,
+            renter: None,
+            version: 1,
+            previous_hash: None,
+            cooldown_until: None,
+        }
+    );
+
+    // Once the expiration has passed the pixel becomes available again
+    let mut later = env.clone();
+    later.block.time = later.block.time.plus_seconds(3600);
+    let res: PixelResponse =
+        from_json(query(deps.as_ref(), later, QueryMsg::Pixel { x: 5, y: 0 }).unwrap()).unwrap();
+    assert_eq!(res.color, [255, 0, 0]);
+    assert!(res.is_available);
+
+    // Coordinates outside the canvas are rejected
+    query(deps.as_ref(), env, QueryMsg::Pixel { x: DEFAULT_CANVAS_WIDTH, y: 0 }).unwrap_err();
+}
+
+#[test]
+fn query_tiles() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_tile(&mut deps, "0", "holder");
+    let env = mock_env();
+    let info = mock_info("holder", &[]);
+
+    // Minted tiles start out with default pixels, unminted tiles do not exist
+    query(deps.as_ref(), env.clone(), QueryMsg::Tile { token_id: "1".to_string() }).unwrap_err();
+    let tile: TileResponse = from_json(
+        query(deps.as_ref(), env.clone(), QueryMsg::Tile { token_id: "0".to_string() }).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tile.owner, "holder");
+    assert_eq!(tile.pixels.len(), PIXELS_PER_TILE as usize);
+    assert_eq!((tile.pixels[42].x, tile.pixels[42].y), (2, 4));
+    assert!(tile.pixels.iter().all(|pixel| pixel.is_available && pixel.version == 0));
+
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x: 2,
+            y: 4,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color: PixelColor::Rgb([0, 255, 0]),
+            expiration: env.block.time.seconds() + 60,
+        },
+    };
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let tile: TileResponse = from_json(
+        query(deps.as_ref(), env.clone(), QueryMsg::Tile { token_id: "0".to_string() }).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tile.owner, "holder");
+    assert_eq!(tile.pixels.len(), PIXELS_PER_TILE as usize);
+    assert_eq!((tile.pixels[42].x, tile.pixels[42].y), (2, 4));
+    assert_eq!(tile.pixels[42].color, [0, 255, 0]);
+    assert!(!tile.pixels[42].is_available);
+    assert!(tile.pixels[41].is_available);
+
+    let res: TilesResponse = from_json(
+        query(deps.as_ref(), env.clone(), QueryMsg::Tiles { start_after: None, limit: None })
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.tiles, vec![tile]);
+
+    let res: TilesResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Tiles {
+                start_after: Some("0".to_string()),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.tiles.is_empty());
+
+    // The owner is whoever holds the tile NFT
+    let transfer = sg721::ExecuteMsg::TransferNft { recipient: "buyer".to_string(), token_id: "0".to_string() };
+    execute(deps.as_mut(), mock_env(), mock_info("holder", &[]), transfer).unwrap();
+    let tile: TileResponse = from_json(
+        query(deps.as_ref(), mock_env(), QueryMsg::Tile { token_id: "0".to_string() }).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tile.owner, "buyer");
+}
+
+#[test]
+fn canvas_coordinates_round_trip() {
+    let canvas = Canvas::default();
+
+    let position = canvas.position(25, 13).unwrap();
+    assert_eq!(position, PixelPosition { tile_id: 1002, offset: 35 });
+    assert_eq!(canvas.coordinates(position), Some((25, 13)));
+
+    let last = canvas.position(canvas.width - 1, canvas.height - 1).unwrap();
+    assert_eq!(last.tile_id, canvas.total_tiles().unwrap() - 1);
+    assert_eq!(last.offset, canvas.pixels_per_tile().unwrap() - 1);
+
+    assert_eq!(canvas.position(canvas.width, 0), None);
+    assert_eq!(canvas.position(0, canvas.height), None);
+    assert_eq!(
+        canvas.coordinates(PixelPosition { tile_id: 0, offset: PIXELS_PER_TILE }),
+        None
+    );
+}
+
+#[test]
+fn set_pixel_color_paints_any_tile() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+    let info = mock_info(MINTER, &[]);
+
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x: 25,
+            y: 13,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color: PixelColor::Rgb([0, 0, 255]),
+            expiration: env.block.time.seconds() + 60,
+        },
+    };
+
+    // Only minted tiles can be painted
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::TileNotMinted { token_id: "1002".to_string() });
+    mint_tile(&mut deps, "1002", MINTER);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "token_id" && attr.value == "1002"));
+
+    let tile: TileResponse = from_json(
+        query(deps.as_ref(), env.clone(), QueryMsg::Tile { token_id: "1002".to_string() }).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tile.pixels[35].color, [0, 0, 255]);
+    assert_eq!((tile.pixels[35].x, tile.pixels[35].y), (25, 13));
+
+    let pixel: PixelResponse =
+        from_json(query(deps.as_ref(), env, QueryMsg::Pixel { x: 25, y: 13 }).unwrap()).unwrap();
+    assert_eq!(pixel.color, [0, 0, 255]);
+}
+
+#[test]
+fn update_canvas() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+
+    let canvas = Canvas {
+        width: 100,
+        height: 50,
+        tile_size: 10,
+    };
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::UpdateCanvas { canvas },
+    };
+
+    // Only the minter can resize the canvas
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::UnauthorizedOwner {});
+
+    // Dimensions must be a multiple of the tile size
+    let invalid = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::UpdateCanvas {
+            canvas: Canvas {
+                width: 105,
+                ..canvas
+            },
+        },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), invalid).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidCanvas(_)));
+
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg.clone()).unwrap();
+    let res: Canvas = from_json(query(deps.as_ref(), env.clone(), QueryMsg::Canvas {}).unwrap()).unwrap();
+    assert_eq!(res, canvas);
+
+    // Painting locks the canvas dimensions
+    mint_tile(&mut deps, "49", MINTER);
+    let paint = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x: 99,
+            y: 49,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color: PixelColor::Rgb([1, 2, 3]),
+            expiration: env.block.time.seconds() + 60,
+        },
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "token_id" && attr.value == "49"));
+
+    let err = execute(deps.as_mut(), env, mock_info(MINTER, &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidCanvas(_)));
+}
+
+#[test]
+fn set_pixel_colors_batch() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_tile(&mut deps, "0", MINTER);
+    mint_tile(&mut deps, "1", MINTER);
+    let env = mock_env();
+    let expiration = env.block.time.seconds() + 60;
+
+    // Pixels spread over two tiles
+    let updates = vec![
+        PixelUpdate { x: 0, y: 0, color: [1, 1, 1], expiration },
+        PixelUpdate { x: 1, y: 0, color: [2, 2, 2], expiration },
+        PixelUpdate { x: 10, y: 0, color: [3, 3, 3], expiration },
+    ];
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColors { updates },
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "pixel_count" && attr.value == "3"));
+    assert!(res.attributes.iter().any(|attr| attr.key == "tile_count" && attr.value == "2"));
+
+    let res: TilesResponse = from_json(
+        query(deps.as_ref(), env.clone(), QueryMsg::Tiles { start_after: None, limit: None })
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.tiles.len(), 2);
+    assert_eq!(res.tiles[0].pixels[0].color, [1, 1, 1]);
+    assert_eq!(res.tiles[0].pixels[1].color, [2, 2, 2]);
+    assert_eq!(res.tiles[1].pixels[0].color, [3, 3, 3]);
+}
+
+#[test]
+fn set_pixel_colors_is_atomic() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_tile(&mut deps, "0", MINTER);
+    let env = mock_env();
+    let expiration = env.block.time.seconds() + 60;
+
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColors {
+            updates: vec![PixelUpdate { x: 5, y: 5, color: [9, 9, 9], expiration }],
+        },
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap();
+
+    // The second pixel is still locked, so the first one must not be painted either
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColors {
+            updates: vec![
+                PixelUpdate { x: 4, y: 5, color: [1, 2, 3], expiration },
+                PixelUpdate { x: 5, y: 5, color: [1, 2, 3], expiration },
+            ],
+        },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPixelUpdate(_)));
+
+    let pixel: PixelResponse =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x: 4, y: 5 }).unwrap())
+            .unwrap();
+    assert_eq!(pixel.color, [0, 0, 0]);
+
+    // Painting the same pixel twice in one batch is rejected as well
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColors {
+            updates: vec![
+                PixelUpdate { x: 0, y: 0, color: [1, 2, 3], expiration },
+                PixelUpdate { x: 0, y: 0, color: [4, 5, 6], expiration },
+            ],
+        },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPixelUpdate(_)));
+
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColors { updates: vec![] },
+    };
+    let err = execute(deps.as_mut(), env, mock_info(MINTER, &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPixelUpdate(_)));
+}
+
+#[test]
+fn fill_rect() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_tile(&mut deps, "0", MINTER);
+    mint_tile(&mut deps, "1", MINTER);
+    let env = mock_env();
+    let expiration = env.block.time.seconds() + 60;
+
+    // A 4x3 rectangle straddling two tiles
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::FillRect {
+            x: 8,
+            y: 2,
+            width: 4,
+            height: 3,
+            color: [7, 7, 7],
+            expiration,
+        },
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "pixel_count" && attr.value == "12"));
+    assert!(res.attributes.iter().any(|attr| attr.key == "tile_count" && attr.value == "2"));
+
+    for (x, y) in [(8, 2), (11, 4), (9, 3)] {
+        let pixel: PixelResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x, y }).unwrap()).unwrap();
+        assert_eq!(pixel.color, [7, 7, 7]);
+    }
+    for (x, y) in [(7, 2), (12, 2), (8, 5)] {
+        let pixel: PixelResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x, y }).unwrap()).unwrap();
+        assert_eq!(pixel.color, [0, 0, 0]);
+    }
+
+    // Overlapping a locked pixel fails the whole fill
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::FillRect {
+            x: 0,
+            y: 0,
+            width: 9,
+            height: 9,
+            color: [1, 1, 1],
+            expiration,
+        },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPixelUpdate(_)));
+
+    // Rectangles must fit on the canvas
+    mint_tile(&mut deps, &Canvas::default().position(DEFAULT_CANVAS_WIDTH - 1, 0).unwrap().token_id(), MINTER);
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::FillRect {
+            x: DEFAULT_CANVAS_WIDTH - 1,
+            y: 0,
+            width: 2,
+            height: 1,
+            color: [1, 1, 1],
+            expiration,
+        },
+    };
+    let err = execute(deps.as_mut(), env, mock_info(MINTER, &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::PixelOutOfRange {});
+}
+
+#[test]
+fn paint_runs() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_tile(&mut deps, "3002", MINTER);
+    let env = mock_env();
+    let expiration = env.block.time.seconds() + 60;
+
+    // A 3 pixel wide sprite: red row, then a transparent pixel and two blue ones
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::PaintRuns {
+            x: 20,
+            y: 30,
+            width: 3,
+            runs: vec![
+                PaintRun { length: 3, color: Some([255, 0, 0]) },
+                PaintRun { length: 1, color: None },
+                PaintRun { length: 2, color: Some([0, 0, 255]) },
+            ],
+            expiration,
+        },
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "action" && attr.value == "paint_runs"));
+    assert!(res.attributes.iter().any(|attr| attr.key == "pixel_count" && attr.value == "5"));
+
+    let expected = [
+        ((20, 30), [255, 0, 0]),
+        ((22, 30), [255, 0, 0]),
+        ((20, 31), [0, 0, 0]),
+        ((21, 31), [0, 0, 255]),
+        ((22, 31), [0, 0, 255]),
+        ((23, 30), [0, 0, 0]),
+    ];
+    for ((x, y), color) in expected {
+        let pixel: PixelResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x, y }).unwrap()).unwrap();
+        assert_eq!(pixel.color, color, "pixel ({x}, {y})");
+    }
+
+    // Runs that only skip pixels paint nothing
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::PaintRuns {
+            x: 0,
+            y: 0,
+            width: 3,
+            runs: vec![PaintRun { length: 9, color: None }],
+            expiration,
+        },
+    };
+    let err = execute(deps.as_mut(), env, mock_info(MINTER, &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPixelUpdate(_)));
+}
+
+#[test]
+fn rent_pixels() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+    let day = env.block.time.seconds() + 86_400;
+    let paint = |x: u32, expiration: u64| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColors {
+            updates: vec![PixelUpdate { x, y: 0, color: [7, 7, 7], expiration }],
+        },
+    };
+
+    // The tile NFT holder owns the tile and paints for free
+    let err = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), paint(0, day)).unwrap_err();
+    assert_eq!(err, ContractError::TileNotMinted { token_id: "0".to_string() });
+    mint_tile(&mut deps, "0", "owner");
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), paint(0, day)).unwrap();
+
+    let price: Coin = from_json(query(deps.as_ref(), env.clone(), QueryMsg::RentalPrice {}).unwrap()).unwrap();
+    assert_eq!(price, Coin { denom: "ustars".to_string(), amount: Uint128::from(100_000u128) });
+
+    // Anyone else has to pay the exact rent, which goes to the tile owner
+    let err = execute(deps.as_mut(), env.clone(), mock_info("renter", &[]), paint(1, day)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFee { .. }));
+    let err = execute(deps.as_mut(), env.clone(), mock_info("renter", &coins(100_001, "ustars")), paint(1, day)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFee { .. }));
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("renter", &coins(100_000, "ustars")), paint(1, day)).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        BankMsg::Send { to_address: "owner".to_string(), amount: coins(100_000, "ustars") }.into()
+    );
+    let pixel: PixelResponse =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x: 1, y: 0 }).unwrap()).unwrap();
+    assert_eq!(pixel.renter, Some("renter".to_string()));
+
+    // Short rentals are rounded up rather than free
+    let msg = paint(2, env.block.time.seconds() + 1);
+    execute(deps.as_mut(), env.clone(), mock_info("renter", &coins(2, "ustars")), msg).unwrap();
+
+    // Rented pixels are locked for everyone, including the tile owner
+    let err = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), paint(1, day)).unwrap_err();
+    assert_eq!(err, ContractError::PixelLocked { renter: "renter".to_string(), expiration: day });
+
+    // Pixels painted by the owner cannot be taken over before they expire
+    let err = execute(deps.as_mut(), env.clone(), mock_info("renter", &coins(100_000, "ustars")), paint(0, day)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Only the owner can change the price
+    let msg = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::UpdateRentalPrice { price: Coin { denom: "ustars".to_string(), amount: Uint128::zero() } },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("renter", &[]), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::UnauthorizedOwner {});
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), msg).unwrap();
+
+    // Free rentals send nothing
+    let res = execute(deps.as_mut(), env, mock_info("renter", &[]), paint(3, day)).unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn pixel_update_event() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_tile(&mut deps, "0", MINTER);
+    let mut env = mock_env();
+    let paint = |color: [u8; 3], expiration: u64| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x: 3,
+            y: 2,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color: PixelColor::Rgb(color),
+            expiration,
+        },
+    };
+
+    let expiration = env.block.time.seconds() + 60;
+    let res = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint([1, 2, 3], expiration)).unwrap();
+    let event = &res.events[0];
+    assert_eq!(event.ty, EVENT_TYPE_PIXEL_UPDATE);
+    let attr = |key: &str| event.attributes.iter().find(|attr| attr.key == key).map(|attr| attr.value.clone());
+    assert_eq!(attr("event_version").unwrap(), "1");
+    assert_eq!(attr("pixel_id").unwrap(), (2 * DEFAULT_CANVAS_WIDTH as u64 + 3).to_string());
+    assert_eq!((attr("color_r").unwrap(), attr("color_g").unwrap(), attr("color_b").unwrap()), ("1".to_string(), "2".to_string(), "3".to_string()));
+    assert_eq!(attr("version").unwrap(), "1");
+    assert_eq!(attr("previous_hash"), None);
+
+    // The hash matches the listener's sha256("pixel_v1_" + JSON) over the metadata
+    let metadata = PixelMetadata {
+        color: Color { r: 1, g: 2, b: 3 },
+        version: 1,
+        previous_hash: None,
+        timestamp: env.block.time.seconds(),
+        expiration: Some(expiration),
+    };
+    let mut hasher = Sha256::new();
+    hasher.update(b"pixel_v1_");
+    hasher.update(serde_json::to_vec(&metadata).unwrap());
+    let first_hash = HexBinary::from(hasher.finalize().as_slice()).to_hex();
+    assert_eq!(attr("metadata_hash").unwrap(), first_hash);
+
+    // Repainting links to the previous version
+    env.block.time = env.block.time.plus_seconds(60);
+    let res = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint([4, 5, 6], expiration + 60)).unwrap();
+    let event = &res.events[0];
+    let attr = |key: &str| event.attributes.iter().find(|attr| attr.key == key).map(|attr| attr.value.clone());
+    assert_eq!(attr("version").unwrap(), "2");
+    assert_eq!(attr("previous_hash").unwrap(), first_hash);
+
+    // The chain is stored with the pixel itself
+    let pixel: PixelResponse =
+        from_json(query(deps.as_ref(), env, QueryMsg::Pixel { x: 3, y: 2 }).unwrap()).unwrap();
+    assert_eq!(pixel.version, 2);
+    assert_eq!(pixel.previous_hash, Some(first_hash));
+}
+
+#[test]
+fn tiles_are_packed() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_tile(&mut deps, "0", MINTER);
+    let mut env = mock_env();
+    let paint = |color: [u8; 3], expiration: u64| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x: 3,
+            y: 2,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color: PixelColor::Rgb(color),
+            expiration,
+        },
+    };
+    let expiration = env.block.time.seconds() + 60;
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint([1, 2, 3], expiration)).unwrap();
+    env.block.time = env.block.time.plus_seconds(60);
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint([4, 5, 6], expiration + 60)).unwrap();
+
+    let (token_id, mut tile) = TILES
+        .range(&deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .next()
+        .unwrap()
+        .unwrap();
+    let pixel = tile.pixels.iter().find(|pixel| pixel.version == 2).unwrap();
+    assert_eq!(pixel.color, [4, 5, 6]);
+    assert!(pixel.previous_hash.is_some());
+
+    // Every field survives the packed layout
+    tile.pixels[0].renter = Some("renter".to_string());
+    tile.pixels[0].expiration = u64::MAX;
+    let packed = tile.to_packed(None).unwrap();
+    TILES.save(&mut deps.storage, token_id.clone(), &tile).unwrap();
+    assert_eq!(TILES.load(&deps.storage, token_id).unwrap(), tile);
+
+    // A fraction of the JSON layout it replaces
+    let json = cosmwasm_std::to_json_vec(&tile).unwrap();
+    assert!(packed.len() * 8 < json.len(), "packed {} bytes, JSON {} bytes", packed.len(), json.len());
+}
+
+#[test]
+fn palette_mode() {
+    let mut deps = mock_dependencies_with_querier();
+    let palette = vec![[0, 0, 0], [255, 0, 0], [0, 0, 255]];
+    setup_contract_with_palette(&mut deps, Some(palette.clone()));
+    mint_tile(&mut deps, "0", MINTER);
+    let env = mock_env();
+    let expiration = env.block.time.seconds() + 60;
+    let paint = |x: u32, color: PixelColor| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x,
+            y: 0,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color,
+            expiration,
+        },
+    };
+
+    // Colors are given as RGB or as an index
+    assert_eq!(from_json::<PixelColor>(b"[1,2,3]").unwrap(), PixelColor::Rgb([1, 2, 3]));
+    assert_eq!(from_json::<PixelColor>(b"1").unwrap(), PixelColor::Index(1));
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(0, PixelColor::Index(1))).unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(1, PixelColor::Rgb([0, 0, 255]))).unwrap();
+
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(2, PixelColor::Rgb([1, 2, 3]))).unwrap_err();
+    assert_eq!(err, ContractError::ColorNotInPalette { color: [1, 2, 3] });
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(2, PixelColor::Index(3))).unwrap_err();
+    assert_eq!(err, ContractError::InvalidPaletteIndex { index: 3 });
+    let batch = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColors {
+            updates: vec![PixelUpdate { x: 2, y: 0, color: [1, 2, 3], expiration }],
+        },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), batch).unwrap_err();
+    assert_eq!(err, ContractError::ColorNotInPalette { color: [1, 2, 3] });
+
+    // Queries resolve the stored indexes
+    let pixel = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, x: u32| -> PixelResponse {
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x, y: 0 }).unwrap()).unwrap()
+    };
+    assert_eq!(pixel(&deps, 0).color, [255, 0, 0]);
+    assert_eq!(pixel(&deps, 1).color, [0, 0, 255]);
+    let (_, tile) = TILES
+        .range(&deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .next()
+        .unwrap()
+        .unwrap();
+    assert!(tile.to_packed(Some(&palette)).unwrap().len() < tile.to_packed(None).unwrap().len());
+
+    // The owner can only append colors, so painted pixels keep theirs
+    let update = |palette: Vec<[u8; 3]>| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::UpdatePalette { palette },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), update(vec![[1, 1, 1]])).unwrap_err();
+    assert_eq!(err, ContractError::UnauthorizedOwner {});
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(vec![[1, 1, 1]])).unwrap_err();
+    assert_eq!(err, ContractError::InvalidPalette("Colors can only be appended to the palette".to_string()));
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(vec![[1, 1, 1], [1, 1, 1]])).unwrap_err();
+    assert_eq!(err, ContractError::InvalidPalette("Duplicate color [1, 1, 1]".to_string()));
+    let replaced = vec![[0, 0, 0], [0, 255, 0], [0, 0, 255], [9, 9, 9]];
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(replaced)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidPalette("Colors can only be appended to the palette".to_string()));
+    let new_palette = vec![[0, 0, 0], [255, 0, 0], [0, 0, 255], [9, 9, 9]];
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(new_palette.clone())).unwrap();
+    assert_eq!(pixel(&deps, 0).color, [255, 0, 0]);
+    let stored: Option<Vec<[u8; 3]>> = from_json(query(deps.as_ref(), env.clone(), QueryMsg::Palette {}).unwrap()).unwrap();
+    assert_eq!(stored, Some(new_palette.clone()));
+
+    // Freezing the collection info freezes the palette
+    let freeze = sg721::ExecuteMsg::Extension { msg: CustomExecuteMsg::FreezeCollectionInfo {} };
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), freeze).unwrap();
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), update(new_palette)).unwrap_err();
+    assert_eq!(err, ContractError::CollectionInfoFrozen {});
+
+    // Indexes need a palette
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let err = execute(deps.as_mut(), env, mock_info(MINTER, &[]), paint(0, PixelColor::Index(0))).unwrap_err();
+    assert_eq!(err, ContractError::PaletteDisabled {});
+}
+
+#[test]
+fn pause_status() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_tile(&mut deps, "0", MINTER);
+    let env = mock_env();
+    let pause = |status: PauseStatus| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::UpdatePauseStatus { status },
+    };
+    let paint = || sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x: 0,
+            y: 0,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color: PixelColor::Rgb([1, 2, 3]),
+            expiration: env.block.time.seconds() + 60,
+        },
+    };
+    let mint = || sg721::ExecuteMsg::Mint {
+        token_id: "1".to_string(),
+        owner: MINTER.to_string(),
+        token_uri: None,
+        extension: None,
+    };
+    let transfer = || sg721::ExecuteMsg::TransferNft {
+        recipient: "recipient".to_string(),
+        token_id: "1".to_string(),
+    };
+
+    // Only the owner can pause
+    let all = PauseStatus { painting: true, minting: true, transfers: true };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), pause(all.clone())).unwrap_err();
+    assert_eq!(err, ContractError::UnauthorizedOwner {});
+
+    // Each flag halts its own actions
+    let painting = PauseStatus { painting: true, ..PauseStatus::default() };
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), pause(painting.clone())).unwrap();
+    let status: PauseStatus = from_json(query(deps.as_ref(), env.clone(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+    assert_eq!(status, painting);
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint()).unwrap_err();
+    assert_eq!(err, ContractError::Paused { action: "painting".to_string() });
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), mint()).unwrap();
+
+    let minting = PauseStatus { minting: true, ..PauseStatus::default() };
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), pause(minting)).unwrap();
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), mint()).unwrap_err();
+    assert_eq!(err, ContractError::Paused { action: "minting".to_string() });
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint()).unwrap();
+
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), pause(all)).unwrap();
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), transfer()).unwrap_err();
+    assert_eq!(err, ContractError::Paused { action: "transfers".to_string() });
+
+    // Resuming restores every action
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), pause(PauseStatus::default())).unwrap();
+    execute(deps.as_mut(), env, mock_info(MINTER, &[]), transfer()).unwrap();
+}
+
+#[test]
+fn rate_limit() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    mint_tile(&mut deps, "0", MINTER);
+    mint_tile(&mut deps, "1", MINTER);
+    let mut env = mock_env();
+    let limit = |rate_limit: Option<RateLimit>| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::UpdateRateLimit { rate_limit },
+    };
+    let paint = |env: &Env, x: u32, count: u32| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColors {
+            updates: (x..x + count)
+                .map(|x| PixelUpdate { x, y: 0, color: [1, 2, 3], expiration: env.block.time.seconds() + 10 })
+                .collect(),
+        },
+    };
+    let allowance = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, env: &Env| -> PaintAllowanceResponse {
+        let msg = QueryMsg::PaintAllowance { address: MINTER.to_string() };
+        from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap()
+    };
+    assert_eq!(allowance(&deps, &env), PaintAllowanceResponse { remaining: None, available_in: None });
+
+    // Only the owner can set a valid limit
+    let rate_limit = RateLimit { max_updates: 3, window: Duration::Time(60), pixel_cooldown: 45 };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), limit(Some(rate_limit.clone()))).unwrap_err();
+    assert_eq!(err, ContractError::UnauthorizedOwner {});
+    let invalid = RateLimit { max_updates: 0, ..rate_limit.clone() };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), limit(Some(invalid))).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidRateLimit(_)));
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), limit(Some(rate_limit.clone()))).unwrap();
+    let res: Option<RateLimit> = from_json(query(deps.as_ref(), env.clone(), QueryMsg::RateLimit {}).unwrap()).unwrap();
+    assert_eq!(res, Some(rate_limit));
+
+    // Updates count per pixel within the window
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(&env, 0, 2)).unwrap();
+    assert_eq!(allowance(&deps, &env), PaintAllowanceResponse { remaining: Some(1), available_in: None });
+    env.block.time = env.block.time.plus_seconds(20);
+    env.block.height += 4;
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(&env, 2, 1)).unwrap();
+    assert_eq!(
+        allowance(&deps, &env),
+        PaintAllowanceResponse { remaining: Some(0), available_in: Some(Duration::Time(40)) }
+    );
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(&env, 3, 1)).unwrap_err();
+    assert_eq!(err, ContractError::RateLimitExceeded { max_updates: 3, window: Duration::Time(60) });
+
+    // Expired pixels stay blocked until their cooldown ends
+    let pixel: PixelResponse = from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x: 2, y: 0 }).unwrap()).unwrap();
+    assert_eq!(pixel.cooldown_until, Some(env.block.time.seconds() + 45));
+    env.block.time = env.block.time.plus_seconds(40);
+    assert_eq!(allowance(&deps, &env), PaintAllowanceResponse { remaining: Some(2), available_in: None });
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(&env, 1, 2)).unwrap_err();
+    assert_eq!(err, ContractError::PixelCooldown { until: env.block.time.seconds() + 5 });
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(&env, 0, 2)).unwrap();
+
+    // Removing the limit lifts both restrictions
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), limit(None)).unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(&env, 2, 10)).unwrap();
+    assert_eq!(allowance(&deps, &env), PaintAllowanceResponse { remaining: None, available_in: None });
+
+    // Windows reaching past the end of time never free up
+    let endless = RateLimit { max_updates: 1, window: Duration::Time(u64::MAX), pixel_cooldown: 0 };
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), limit(Some(endless))).unwrap();
+    assert_eq!(
+        allowance(&deps, &env),
+        PaintAllowanceResponse { remaining: Some(0), available_in: Some(Duration::Time(u64::MAX - env.block.time.seconds())) }
+    );
+}
+
+#[test]
+fn coloring_rights() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let mut env = mock_env();
+    let paint = |env: &Env, x: u32| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x,
+            y: 0,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color: PixelColor::Rgb([1, 2, 3]),
+            expiration: env.block.time.seconds() + 60,
+        },
+    };
+    let grant = |expires: Option<Expiration>| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::GrantColoringRights {
+            token_id: "0".to_string(),
+            grantee: "bot".to_string(),
+            expires,
+        },
+    };
+    let grants = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, env: &Env, include_expired: bool| {
+        let msg = QueryMsg::ColoringRights { token_id: "0".to_string(), include_expired: Some(include_expired) };
+        let res: ColoringRightsResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        res.grants
+    };
+
+    // Only minted tiles take grants
+    let expires = Expiration::AtHeight(env.block.height + 10);
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), grant(Some(expires))).unwrap_err();
+    assert_eq!(err, ContractError::TileNotMinted { token_id: "0".to_string() });
+    mint_tile(&mut deps, "0", MINTER);
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(&env, 0)).unwrap();
+
+    // Only the tile owner grants rights, and never already expired ones
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), grant(Some(expires))).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let expired = Expiration::AtHeight(env.block.height);
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), grant(Some(expired))).unwrap_err();
+    assert_eq!(err, ContractError::InvalidExpiration {});
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), grant(Some(expires))).unwrap();
+    assert_eq!(grants(&deps, &env, false), vec![ColoringGrant { grantee: "bot".to_string(), expires }]);
+
+    // Grantees paint for free while others rent
+    execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), paint(&env, 1)).unwrap();
+    let pixel: PixelResponse = from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x: 1, y: 0 }).unwrap()).unwrap();
+    assert_eq!(pixel.renter, None);
+    let err = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), paint(&env, 2)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFee { .. }));
+
+    // Expired grants no longer apply and are hidden by default
+    env.block.height += 10;
+    let err = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), paint(&env, 2)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFee { .. }));
+    assert_eq!(grants(&deps, &env, false), vec![]);
+    assert_eq!(grants(&deps, &env, true).len(), 1);
+
+    // Revoking removes the grant
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), grant(None)).unwrap();
+    let revoke = sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::RevokeColoringRights { token_id: "0".to_string(), grantee: "bot".to_string() },
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), revoke.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), revoke.clone()).unwrap();
+    assert_eq!(grants(&deps, &env, true), vec![]);
+
+    // Grants of a previous holder are void once the tile is transferred
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), grant(None)).unwrap();
+    let transfer = sg721::ExecuteMsg::TransferNft { recipient: "holder".to_string(), token_id: "0".to_string() };
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), transfer).unwrap();
+    assert_eq!(grants(&deps, &env, true), vec![]);
+    let err = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), paint(&env, 3)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFee { .. }));
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), grant(None)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Operators of the holder manage rights like cw721 approvals
+    let err = execute(deps.as_mut(), env.clone(), mock_info("operator", &[]), grant(None)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let approve_all = sg721::ExecuteMsg::ApproveAll { operator: "operator".to_string(), expires: None };
+    execute(deps.as_mut(), env.clone(), mock_info("holder", &[]), approve_all).unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info("operator", &[]), grant(None)).unwrap();
+    assert_eq!(grants(&deps, &env, false), vec![ColoringGrant { grantee: "bot".to_string(), expires: Expiration::Never {} }]);
+    execute(deps.as_mut(), env.clone(), mock_info("operator", &[]), revoke).unwrap();
+    assert_eq!(grants(&deps, &env, true), vec![]);
+}
+
+#[test]
+fn protected_pixels() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let env = mock_env();
+    let canvas: Canvas = from_json(query(deps.as_ref(), env.clone(), QueryMsg::Canvas {}).unwrap()).unwrap();
+    let paint = |x: u32| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x,
+            y: 0,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color: PixelColor::Rgb([1, 2, 3]),
+            expiration: env.block.time.seconds() + 60,
+        },
+    };
+    let protect = |mask: Vec<u8>| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::UpdateProtectedPixels { token_id: "0".to_string(), mask: Binary::from(mask) },
+    };
+    let mask = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| {
+        let msg = QueryMsg::ProtectedPixels { token_id: "0".to_string() };
+        let res: ProtectedPixelsResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        res.mask.to_vec()
+    };
+
+    // Only minted tiles can be protected
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), protect(vec![])).unwrap_err();
+    assert_eq!(err, ContractError::TileNotMinted { token_id: "0".to_string() });
+    mint_tile(&mut deps, "0", MINTER);
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(0)).unwrap();
+
+    // Protect pixel (1, 0)
+    let offset = canvas.position(1, 0).unwrap().offset as usize;
+    let mut bits = vec![0u8; (canvas.tile_size as usize * canvas.tile_size as usize).div_ceil(8)];
+    bits[offset / 8] |= 1 << (offset % 8);
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), protect(bits.clone())).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), protect(vec![1])).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPixelUpdate(_)));
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), protect(bits.clone())).unwrap();
+    assert_eq!(mask(&deps), bits);
+
+    // Renters are turned away from protected pixels only, the owner still paints them
+    let err = execute(deps.as_mut(), env.clone(), mock_info("renter", &[]), paint(1)).unwrap_err();
+    assert_eq!(err, ContractError::PixelProtected { x: 1, y: 0 });
+    let err = execute(deps.as_mut(), env.clone(), mock_info("renter", &[]), paint(2)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFee { .. }));
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(1)).unwrap();
+
+    // The mask follows the NFT, a previous holder can no longer change it
+    let transfer = sg721::ExecuteMsg::TransferNft { recipient: "holder".to_string(), token_id: "0".to_string() };
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), transfer).unwrap();
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), protect(vec![])).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    assert_eq!(mask(&deps), bits);
+
+    // An empty mask lifts the protection
+    execute(deps.as_mut(), env.clone(), mock_info("holder", &[]), protect(vec![])).unwrap();
+    assert_eq!(mask(&deps), Vec::<u8>::new());
+}
+
+#[test]
+fn expiry_policy() {
+    let mut deps = mock_dependencies_with_querier();
+    setup_contract(&mut deps);
+    let mut env = mock_env();
+    let day = 86_400;
+    let canvas: Canvas = from_json(query(deps.as_ref(), env.clone(), QueryMsg::Canvas {}).unwrap()).unwrap();
+    let paint = |x: u32, color: [u8; 3]| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetPixelColor {
+            x,
+            y: 0,
+            current_tile_metadata: Binary::from(vec![0u8; 32]),
+            color: PixelColor::Rgb(color),
+            expiration: env.block.time.seconds() + day,
+        },
+    };
+    let set_base = |colors: Vec<u8>| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetBaseImage { token_id: "0".to_string(), colors: Binary::from(colors) },
+    };
+    let set_policy = |policy: ExpiryPolicy| sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::UpdateExpiryPolicy { policy },
+    };
+    let color = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, env: &Env, x: u32| {
+        let res: PixelResponse = from_json(query(deps.as_ref(), env.clone(), QueryMsg::Pixel { x, y: 0 }).unwrap()).unwrap();
+        res.color
+    };
+
+    // The holder paints (0, 0) and a renter (1, 0)
+    mint_tile(&mut deps, "0", MINTER);
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), paint(0, [9, 9, 9])).unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info("renter", &coins(100_000, "ustars")), paint(1, [1, 2, 3])).unwrap();
+
+    // Only the tile holder sets a full base image
+    let base = [50, 60, 70].repeat(canvas.tile_size as usize * canvas.tile_size as usize);
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), sg721::ExecuteMsg::Extension {
+        msg: CustomExecuteMsg::SetBaseImage { token_id: "1".to_string(), colors: Binary::from(base.clone()) },
+    })
+    .unwrap_err();
+    assert_eq!(err, ContractError::TileNotMinted { token_id: "1".to_string() });
+    let err = execute(deps.as_mut(), env.clone(), mock_info("renter", &[]), set_base(base.clone())).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), set_base(vec![1, 2, 3])).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPixelUpdate(_)));
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), set_base(base.clone())).unwrap();
+    let msg = QueryMsg::BaseImage { token_id: "0".to_string() };
+    let res: BaseImageResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+    assert_eq!(res.colors, Binary::from(base));
+
+    // Only the owner sets the policy
+    let err = execute(deps.as_mut(), env.clone(), mock_info("renter", &[]), set_policy(ExpiryPolicy::RevertToBase)).unwrap_err();
+    assert_eq!(err, ContractError::UnauthorizedOwner {});
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), set_policy(ExpiryPolicy::RevertToBase)).unwrap();
+    let policy: ExpiryPolicy = from_json(query(deps.as_ref(), env.clone(), QueryMsg::ExpiryPolicy {}).unwrap()).unwrap();
+    assert_eq!(policy, ExpiryPolicy::RevertToBase);
+
+    // Active rentals keep their color
+    assert_eq!(color(&deps, &env, 1), [1, 2, 3]);
+
+    // Expired rentals follow the policy while the owner's own pixels do not
+    env.block.time = env.block.time.plus_seconds(day);
+    assert_eq!(color(&deps, &env, 1), [50, 60, 70]);
+    assert_eq!(color(&deps, &env, 0), [9, 9, 9]);
+    let tile: TileResponse = from_json(query(deps.as_ref(), env.clone(), QueryMsg::Tile { token_id: "0".to_string() }).unwrap()).unwrap();
+    let offset = canvas.position(1, 0).unwrap().offset as usize;
+    assert_eq!(tile.pixels[offset].color, [50, 60, 70]);
+
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), set_policy(ExpiryPolicy::RevertToBlank)).unwrap();
+    assert_eq!(color(&deps, &env, 1), [0, 0, 0]);
+    execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), set_policy(ExpiryPolicy::KeepLastColor)).unwrap();
+    assert_eq!(color(&deps, &env, 1), [1, 2, 3]);
+}
+
+// State layout of 0.1.0, written by a stand-in for the old code
+#[cw_serde]
+struct LegacyPixel {
+    color: [u8; 3],
+    expiration: u64,
+}
+
+#[cw_serde]
+struct LegacyTile {
+    owner: Addr,
+    pixels: Vec<LegacyPixel>,
+}
+
+#[cw_serde]
+struct LegacyInstantiateMsg {
+    contract: String,
+    version: String,
+}
+
+const LEGACY_TILES: Map<String, LegacyTile> = Map::new("tiles");
+
+fn legacy_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: LegacyInstantiateMsg,
+) -> StdResult<Response> {
+    cw2::set_contract_version(deps.storage, msg.contract, msg.version)?;
+    let tile = LegacyTile {
+        owner: Addr::unchecked(MINTER),
+        pixels: vec![LegacyPixel { color: [255, 0, 0], expiration: 42 }; PIXELS_PER_TILE as usize],
+    };
+    LEGACY_TILES.save(deps.storage, "0".to_string(), &tile)?;
+    // 0.1.0 let anyone claim a tile by painting it, without minting its NFT
+    let claimed = LegacyTile {
+        owner: Addr::unchecked("painter"),
+        pixels: vec![LegacyPixel { color: [0, 0, 255], expiration: 7 }; PIXELS_PER_TILE as usize],
+    };
+    LEGACY_TILES.save(deps.storage, "1".to_string(), &claimed)?;
+    // The tile NFT is kept in the cw721 layout shared by every version
+    let token = TokenInfo {
+        owner: Addr::unchecked(MINTER),
+        approvals: vec![],
+        token_uri: None,
+        extension: None,
+    };
+    let cw721 = Sg721Contract::<Option<Empty>>::default();
+    cw721.tokens.save(deps.storage, "0", &token)?;
+    cw721.increment_tokens(deps.storage)?;
+    Ok(Response::new())
+}
+
+fn legacy_execute(_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn legacy_query(_deps: cosmwasm_std::Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    Ok(Binary::default())
+}
+
+/// Instantiates the legacy contract with the given cw2 info and stores the current code
+fn setup_legacy_contract(app: &mut App, contract: &str, version: &str) -> (Addr, u64) {
+    let legacy_code_id = app.store_code(Box::new(ContractWrapper::new(
+        legacy_execute,
+        legacy_instantiate,
+        legacy_query,
+    )));
+    let code_id = app.store_code(Box::new(
+        ContractWrapper::new(execute, instantiate, query).with_migrate(migrate),
+    ));
+    let addr = app
+        .instantiate_contract(
+            legacy_code_id,
+            Addr::unchecked(MINTER),
+            &LegacyInstantiateMsg {
+                contract: contract.to_string(),
+                version: version.to_string(),
+            },
+            &[],
+            "mosaic",
+            Some(MINTER.to_string()),
+        )
+        .unwrap();
+    (addr, code_id)
+}
+
+#[test]
+fn migrate_from_v0_1_0() {
+    let mut app = App::default();
+    let (addr, code_id) = setup_legacy_contract(&mut app, "crates.io:mosaic-nft", "0.1.0");
+
+    let res = app
+        .migrate_contract(Addr::unchecked(MINTER), addr.clone(), &MigrateMsg {}, code_id)
+        .unwrap();
+    let wasm = res.events.iter().find(|event| event.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "migration" && attr.value == "0.2.0"));
+
+    let version = cw2::query_contract_info(&app.wrap(), addr.to_string()).unwrap();
+    assert_eq!(version.contract, CONTRACT_NAME);
+    assert_eq!(version.version, CONTRACT_VERSION);
+
+    // Tiles are packed with versioned pixels
+    let tile: TileResponse = app
+        .wrap()
+        .query_wasm_smart(&addr, &QueryMsg::Tile { token_id: "0".to_string() })
+        .unwrap();
+    assert_eq!(tile.owner, MINTER);
+    assert_eq!(tile.pixels.len(), PIXELS_PER_TILE as usize);
+    assert_eq!(tile.pixels[0].color, [255, 0, 0]);
+    assert_eq!(tile.pixels[0].expiration, 42);
+    assert_eq!(tile.pixels[0].version, 0);
+    assert_eq!(tile.pixels[0].renter, None);
+
+    // Tiles claimed without an NFT get one minted to their first painter
+    let res: TilesResponse = app
+        .wrap()
+        .query_wasm_smart(&addr, &QueryMsg::Tiles { start_after: None, limit: None })
+        .unwrap();
+    assert_eq!(res.tiles.len(), 2);
+    assert_eq!((res.tiles[1].owner.as_str(), res.tiles[1].pixels[0].color), ("painter", [0, 0, 255]));
+    let owner: cw721::OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(&addr, &QueryMsg::OwnerOf { token_id: "1".to_string(), include_expired: None })
+        .unwrap();
+    assert_eq!(owner.owner, "painter");
+    let count: cw721::NumTokensResponse = app.wrap().query_wasm_smart(&addr, &QueryMsg::NumTokens {}).unwrap();
+    assert_eq!(count.count, 2);
+
+    // State added after 0.1.0 gets its defaults
+    let canvas: Canvas = app.wrap().query_wasm_smart(&addr, &QueryMsg::Canvas {}).unwrap();
+    assert_eq!(canvas, Canvas::default());
+    let price: Coin = app.wrap().query_wasm_smart(&addr, &QueryMsg::RentalPrice {}).unwrap();
+    assert_eq!(price, Coin::new(100_000, "ustars"));
+
+    // Migrating again to the same version is a no-op
+    let res = app
+        .migrate_contract(Addr::unchecked(MINTER), addr, &MigrateMsg {}, code_id)
+        .unwrap();
+    let wasm = res.events.iter().find(|event| event.ty == "wasm").unwrap();
+    assert!(!wasm.attributes.iter().any(|attr| attr.key == "migration"));
+}
+
+#[test]
+fn migrate_rejects_downgrade_and_other_contracts() {
+    let mut app = App::default();
+    let (addr, code_id) = setup_legacy_contract(&mut app, CONTRACT_NAME, "99.0.0");
+    let err = app
+        .migrate_contract(Addr::unchecked(MINTER), addr, &MigrateMsg {}, code_id)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MigrationError(format!("cannot downgrade from 99.0.0 to {CONTRACT_VERSION}"))
+    );
+
+    // The mosaic contract and the shared 0.1.0 name past 0.1.0 are other contracts
+    for (contract, version) in [
+        ("crates.io:other", "0.1.0"),
+        ("crates.io:mosaic-tiles", CONTRACT_VERSION),
+        ("crates.io:mosaic-nft", "0.2.0"),
+    ] {
+        let (addr, code_id) = setup_legacy_contract(&mut app, contract, version);
+        let err = app
+            .migrate_contract(Addr::unchecked(MINTER), addr, &MigrateMsg {}, code_id)
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::MigrationError(format!("cannot migrate from contract {contract}"))
+        );
+    }
+}